@@ -0,0 +1,117 @@
+use cursive::View;
+use cursive::view::{Margins, ViewWrapper};
+use cursive::views::PaddedView;
+use cursive_aligned_view::AlignedView;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Alignment {
+    #[serde(alias = "default")]
+    #[default]
+    TopLeft,
+    #[serde(alias = "top")]
+    TopCenter,
+    TopRight,
+    #[serde(alias = "left")]
+    CenterLeft,
+    Center,
+    #[serde(alias = "right")]
+    CenterRight,
+    BottomLeft,
+    #[serde(alias = "bottom")]
+    BottomCenter,
+    BottomRight,
+}
+
+impl Alignment {
+    fn is_centered_horizontally(self) -> bool {
+        matches!(
+            self,
+            Alignment::TopCenter | Alignment::Center | Alignment::BottomCenter
+        )
+    }
+
+    fn is_centered_vertically(self) -> bool {
+        matches!(
+            self,
+            Alignment::CenterLeft | Alignment::Center | Alignment::CenterRight
+        )
+    }
+}
+
+/// Where on the screen something goes: one of nine anchors, then moved by whole cells.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(from = "RawAnchor")]
+pub struct Anchor {
+    pub alignment: Option<Alignment>,
+    /// Columns to the right, or to the left if negative.
+    pub offset_x: i32,
+    /// Rows down, or up if negative.
+    pub offset_y: i32,
+}
+
+/// `align` can't be an alias, serde leaves those out when the struct is flattened.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAnchor {
+    alignment: Option<Alignment>,
+    align: Option<Alignment>,
+    #[serde(default)]
+    offset_x: i32,
+    #[serde(default)]
+    offset_y: i32,
+}
+
+impl From<RawAnchor> for Anchor {
+    fn from(raw: RawAnchor) -> Self {
+        Anchor {
+            alignment: raw.alignment.or(raw.align),
+            offset_x: raw.offset_x,
+            offset_y: raw.offset_y,
+        }
+    }
+}
+
+impl Anchor {
+    /// Moving the view is done with a margin on the side it moves away from.
+    fn margins(&self, alignment: Alignment) -> Margins {
+        // A centered view only moves by half its margin.
+        let split = |offset: i32, centered: bool| {
+            let amount = offset.unsigned_abs() as usize * if centered { 2 } else { 1 };
+            if offset >= 0 {
+                (amount, 0)
+            } else {
+                (0, amount)
+            }
+        };
+        let (left, right) = split(self.offset_x, alignment.is_centered_horizontally());
+        let (top, bottom) = split(self.offset_y, alignment.is_centered_vertically());
+        Margins::lrtb(left, right, top, bottom)
+    }
+
+    /// Places the view, `alignment` being used if the anchor has none.
+    pub fn apply<V: View>(&self, view: &mut AlignedView<PaddedView<V>>, alignment: Alignment) {
+        let alignment = self.alignment.unwrap_or(alignment);
+        match alignment {
+            Alignment::TopLeft => view.set_top_left(),
+            Alignment::TopCenter => view.set_top_center(),
+            Alignment::TopRight => view.set_top_right(),
+            Alignment::CenterLeft => view.set_center_left(),
+            Alignment::Center => view.set_center(),
+            Alignment::CenterRight => view.set_center_right(),
+            Alignment::BottomLeft => view.set_bottom_left(),
+            Alignment::BottomCenter => view.set_bottom_center(),
+            Alignment::BottomRight => view.set_bottom_right(),
+        }
+        let margins = self.margins(alignment);
+        view.with_view_mut(|padded| padded.set_margins(margins));
+    }
+
+    /// A view placed by the anchor, to be laid out over the whole screen.
+    pub fn wrap<V: View>(&self, view: V, alignment: Alignment) -> AlignedView<PaddedView<V>> {
+        let mut aligned = AlignedView::with_top_left(PaddedView::new(Margins::zeroes(), view));
+        self.apply(&mut aligned, alignment);
+        aligned
+    }
+}
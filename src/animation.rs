@@ -0,0 +1,121 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::canvas::Canvas;
+use crate::frame::Frame;
+
+/// Shows frames one after another on a layer, from whichever thread plays it.
+#[derive(Clone)]
+pub struct Playback {
+    canvas: Canvas,
+    layer: String,
+    /// Held while a frame is shown, so nothing shows up once stopped.
+    stopped: Arc<Mutex<bool>>,
+}
+
+impl Playback {
+    pub fn new(canvas: Canvas, layer: &str) -> Self {
+        Playback {
+            canvas,
+            layer: layer.to_string(),
+            stopped: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Makes the playback stop before its next frame.
+    pub fn stop(&self) {
+        *self.stopped.lock().unwrap() = true;
+    }
+
+    /// Returns `false` if the playback was stopped.
+    fn show(&self, frame: Option<Frame>) -> bool {
+        let stopped = self.stopped.lock().unwrap();
+        if *stopped {
+            return false;
+        }
+        match frame {
+            Some(frame) => self.canvas.set_layer(&self.layer, frame, None),
+            None => {
+                self.canvas.remove_layer(&self.layer);
+            }
+        }
+        true
+    }
+
+    /// Plays the frames after the first one, which is already shown where the layer goes.
+    /// The last one stays unless there's a `duration` after which the layer is removed.
+    pub fn play(&self, frames: &[(Frame, Duration)], looping: bool, duration: Option<Duration>) {
+        // There's nothing to wait for with one frame shown for good.
+        if frames.is_empty() || (frames.len() == 1 && duration.is_none()) {
+            return;
+        }
+        let end = duration.map(|duration| Instant::now() + duration);
+        let left = |delay: Duration| {
+            end.map_or(delay, |end| {
+                delay.min(end.saturating_duration_since(Instant::now()))
+            })
+        };
+        let mut index = 0;
+        loop {
+            crate::common::sleep(left(frames[index].1));
+            if end.is_some_and(|end| Instant::now() >= end) {
+                break;
+            }
+            index = (index + 1) % frames.len();
+            if index == 0 && !looping {
+                match end {
+                    Some(_) => crate::common::sleep(left(Duration::MAX)),
+                    None => return,
+                }
+                break;
+            }
+            if !self.show(Some(frames[index].0.clone())) {
+                return;
+            }
+        }
+        self.show(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cursive::Cursive;
+    use cursive::views::TextContent;
+
+    use super::*;
+
+    fn playback(content: &TextContent) -> Playback {
+        let canvas = Canvas::new(content.clone(), Cursive::new().cb_sink().clone());
+        Playback::new(canvas, "")
+    }
+
+    fn frames(texts: &[&str], delay: u64) -> Vec<(Frame, Duration)> {
+        (texts.iter())
+            .map(|text| (Frame::from_text(text), Duration::from_millis(delay)))
+            .collect()
+    }
+
+    #[test]
+    fn one_frame_is_done_once_shown() {
+        let start = Instant::now();
+        playback(&TextContent::new("")).play(&frames(&["a"], 500), true, None);
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn last_frame_stays() {
+        let content = TextContent::new("");
+        let start = Instant::now();
+        playback(&content).play(&frames(&["a", "b"], 20), false, None);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert_eq!(content.get_content().source(), "b\n");
+    }
+
+    #[test]
+    fn removed_after_the_duration() {
+        let content = TextContent::new("");
+        let duration = Some(Duration::from_millis(30));
+        playback(&content).play(&frames(&["a", "b"], 20), true, duration);
+        assert_eq!(content.get_content().source(), "");
+    }
+}
@@ -0,0 +1,141 @@
+use evalexpr::Value;
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer};
+
+use crate::executable::{Executable, ExecutionState};
+use crate::input_assist::EntryOptions;
+use crate::prompt::RetryOptions;
+
+/// Asks the player a question and stores the parsed answer in a variable.
+/// Global commands aren't run, any input can be an answer (a name could be `inventory`).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ask {
+    pub prompt: Option<String>,
+    #[serde(alias = "var")]
+    pub variable: String,
+    #[serde(rename = "type")]
+    #[serde(default)]
+    pub answer: AnswerType,
+    #[serde(alias = "validation")]
+    #[serde(default)]
+    pub validate: Validation,
+    /// Once the attempts run out, the variable is left as it was.
+    #[serde(flatten)]
+    pub retry: RetryOptions,
+    #[serde(flatten)]
+    pub entry: EntryOptions,
+}
+
+impl Ask {
+    const RETRY_MESSAGE: &'static str = "Invalid answer, try again.";
+}
+
+impl Executable for Ask {
+    fn execute(&self, execution: &mut ExecutionState) {
+        if let Some(ref prompt) = self.prompt {
+            execution.ui.append(execution.state.expand_string(prompt));
+        }
+        let options = self.validate.one_of.clone();
+        execution.ui.set_completions(options.unwrap_or_default());
+        let mut attempts = 0;
+        let value = loop {
+            let input = match execution.ui.get_entry_input(None, false, &self.entry) {
+                Some(input) => input,
+                None => return,
+            };
+            let value = self
+                .answer
+                .parse(input.trim())
+                .filter(|value| self.validate.accepts(value));
+            if let Some(value) = value {
+                break value;
+            }
+            attempts += 1;
+            if !self
+                .retry
+                .again(attempts, Some(Self::RETRY_MESSAGE), execution)
+            {
+                return;
+            }
+        };
+        if let Err(e) = execution.state.set_var(&self.variable, value) {
+            execution.ui.append_err(&e.to_string());
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AnswerType {
+    #[default]
+    String,
+    #[serde(alias = "int")]
+    Integer,
+    #[serde(alias = "number")]
+    Float,
+    #[serde(alias = "yes/no")]
+    #[serde(alias = "bool")]
+    YesNo,
+}
+
+impl AnswerType {
+    pub fn parse(self, input: &str) -> Option<Value> {
+        match self {
+            AnswerType::String => Some(Value::String(input.to_string())),
+            AnswerType::Integer => input.parse().ok().map(Value::Int),
+            AnswerType::Float => input.parse().ok().map(Value::Float),
+            AnswerType::YesNo => match input.to_lowercase().as_str() {
+                "yes" | "y" | "yeah" | "true" => Some(Value::Boolean(true)),
+                "no" | "n" | "nope" | "false" => Some(Value::Boolean(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Rules an answer must follow, the ones that don't apply to its type are ignored.
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Validation {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    #[serde(deserialize_with = "Validation::deserialize_pattern")]
+    pub pattern: Option<Regex>,
+    pub one_of: Option<Vec<String>>,
+}
+
+impl Validation {
+    pub fn accepts(&self, value: &Value) -> bool {
+        match value {
+            Value::String(s) => {
+                let length = s.chars().count();
+                self.min_length.is_none_or(|min| length >= min)
+                    && self.max_length.is_none_or(|max| length <= max)
+                    && self.pattern.as_ref().is_none_or(|re| re.is_match(s))
+                    && self.one_of.as_ref().is_none_or(|options| {
+                        options.iter().any(|option| option.eq_ignore_ascii_case(s))
+                    })
+            }
+            Value::Int(_) | Value::Float(_) => {
+                let n = value.as_number().unwrap();
+                self.min.is_none_or(|min| n >= min) && self.max.is_none_or(|max| n <= max)
+            }
+            _ => true,
+        }
+    }
+
+    fn deserialize_pattern<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Anchored, so the whole answer has to match.
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(Some)
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,204 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cursive::{CbSink, Cursive};
+use cursive::views::TextContent;
+use serde::Deserialize;
+
+use crate::frame::Frame;
+use crate::transition::TransitionEffect;
+
+/// Where a layer is drawn, layers with a higher `z` cover the others.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct Placement {
+    #[serde(default)]
+    pub x: usize,
+    #[serde(default)]
+    pub y: usize,
+    /// Keeps the layer's current one, or 0 for a new layer.
+    pub z: Option<i32>,
+}
+
+struct Layer {
+    name: String,
+    frame: Frame,
+    x: usize,
+    y: usize,
+    z: i32,
+}
+
+/// In the order they were added, which orders layers with the same `z`.
+#[derive(Default)]
+pub struct Layers(Vec<Layer>);
+
+impl Layers {
+    /// Draws the frame on the layer, placed where it was if there's no `placement`.
+    pub fn set(&mut self, name: &str, frame: Frame, placement: Option<Placement>) {
+        match self.0.iter_mut().find(|layer| layer.name == name) {
+            Some(layer) => {
+                layer.frame = frame;
+                if let Some(placement) = placement {
+                    layer.x = placement.x;
+                    layer.y = placement.y;
+                    layer.z = placement.z.unwrap_or(layer.z);
+                }
+            }
+            None => {
+                let placement = placement.unwrap_or_default();
+                self.0.push(Layer {
+                    name: name.to_string(),
+                    frame,
+                    x: placement.x,
+                    y: placement.y,
+                    z: placement.z.unwrap_or(0),
+                });
+            }
+        }
+    }
+
+    /// Returns `false` if there's no such layer.
+    pub fn move_layer(
+        &mut self,
+        name: &str,
+        x: Option<usize>,
+        y: Option<usize>,
+        z: Option<i32>,
+    ) -> bool {
+        let Some(layer) = self.0.iter_mut().find(|layer| layer.name == name) else {
+            return false;
+        };
+        layer.x = x.unwrap_or(layer.x);
+        layer.y = y.unwrap_or(layer.y);
+        layer.z = z.unwrap_or(layer.z);
+        true
+    }
+
+    /// Returns `false` if there's no such layer.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let count = self.0.len();
+        self.0.retain(|layer| layer.name != name);
+        self.0.len() < count
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn compose(&self) -> Frame {
+        let mut ordered: Vec<&Layer> = self.0.iter().collect();
+        ordered.sort_by_key(|layer| layer.z);
+        let mut frame = Frame::default();
+        for layer in ordered {
+            frame.draw(&layer.frame, layer.x, layer.y);
+        }
+        frame
+    }
+}
+
+/// Named layers drawn on top of each other into the frame view.
+#[derive(Clone)]
+pub struct Canvas {
+    content: TextContent,
+    cb_sink: CbSink,
+    layers: Arc<Mutex<Layers>>,
+    /// Set while a transition is drawn, changes made meanwhile show up once it's done.
+    transitioning: Arc<AtomicBool>,
+}
+
+impl Canvas {
+    /// Time between the steps of a transition.
+    const STEP: Duration = Duration::from_millis(40);
+
+    pub fn new(content: TextContent, cb_sink: CbSink) -> Self {
+        Canvas {
+            content,
+            cb_sink,
+            layers: Arc::new(Mutex::new(Layers::default())),
+            transitioning: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Changes the layers, the frame going to what they show next through the transition if given.
+    pub fn change<T>(
+        &self,
+        transition: Option<(TransitionEffect, Duration)>,
+        change: impl FnOnce(&mut Layers) -> T,
+    ) -> T {
+        let mut layers = self.layers.lock().unwrap();
+        let Some((effect, duration)) = transition else {
+            let changed = change(&mut layers);
+            if !self.transitioning.load(Ordering::SeqCst) {
+                self.show(&layers.compose());
+            }
+            return changed;
+        };
+        let from = layers.compose();
+        let changed = change(&mut layers);
+        let to = layers.compose();
+        self.transitioning.store(true, Ordering::SeqCst);
+        // Not holding the layers while waiting, animations keep playing in the background.
+        drop(layers);
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            let progress = start.elapsed().as_secs_f32() / duration.as_secs_f32();
+            self.show(&effect.blend(&from, &to, progress));
+            crate::common::sleep(Self::STEP.min(duration.saturating_sub(start.elapsed())));
+        }
+        let layers = self.layers.lock().unwrap();
+        self.transitioning.store(false, Ordering::SeqCst);
+        self.show(&layers.compose());
+        changed
+    }
+
+    pub fn set_layer(&self, name: &str, frame: Frame, placement: Option<Placement>) {
+        self.change(None, |layers| layers.set(name, frame, placement));
+    }
+
+    pub fn move_layer(
+        &self,
+        name: &str,
+        x: Option<usize>,
+        y: Option<usize>,
+        z: Option<i32>,
+    ) -> bool {
+        self.change(None, |layers| layers.move_layer(name, x, y, z))
+    }
+
+    pub fn remove_layer(&self, name: &str) -> bool {
+        self.change(None, |layers| layers.remove(name))
+    }
+
+    fn show(&self, frame: &Frame) {
+        self.content.set_content(frame.to_styled());
+        let _ = self.cb_sink.send(Box::new(Cursive::noop));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn layers_change_during_a_transition() {
+        let content = TextContent::new("");
+        let canvas = Canvas::new(content.clone(), Cursive::new().cb_sink().clone());
+        let transitioning = canvas.clone();
+        let transition = thread::spawn(move || {
+            let fade = Some((TransitionEffect::Fade, Duration::from_millis(200)));
+            transitioning.change(fade, |layers| layers.set("a", Frame::from_text("a"), None));
+        });
+        crate::common::sleep(Duration::from_millis(50));
+        let start = Instant::now();
+        let placement = Placement {
+            x: 1,
+            ..Placement::default()
+        };
+        canvas.set_layer("b", Frame::from_text("b"), Some(placement));
+        assert!(start.elapsed() < Duration::from_millis(100));
+        transition.join().unwrap();
+        assert_eq!(content.get_content().source(), "ab\n");
+    }
+}
@@ -1,8 +1,15 @@
+use std::path::Path;
+
+use evalexpr::{EvalexprError, EvalexprResult, Value};
+use lazy_static::lazy_static;
+use regex::Regex;
 use serde::Deserialize;
 
 use crate::common::Many;
+use crate::compiled::{Checked, Compiled, CompileError};
 use crate::condition::Condition;
-use crate::initializer::RuntimeState;
+use crate::initializer::{InitializerData, RuntimeState};
+use crate::parser::{Command, Lexicon, Noun};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,19 +17,268 @@ pub enum Capture {
     #[serde(alias = "lit")]
     #[serde(deserialize_with = "Many::deserialize_many")]
     Literals(Vec<String>),
+    #[serde(alias = "num")]
+    Number(NumberCapture),
+    /// Any word of a named vocabulary, turned into literals when compiled.
+    #[serde(alias = "vocab")]
+    Vocabulary(String),
+    #[serde(alias = "cmd")]
+    Command(CommandCapture),
 }
 
 impl Capture {
-    pub fn captures(&self, input: &str) -> bool {
+    /// The exact words it accepts, empty for captures that don't have a fixed set.
+    pub fn literals(&self) -> &[String] {
         match &self {
-            Capture::Literals(literals) =>
-                literals.iter().any(|lit| input.contains(lit))
+            Capture::Literals(literals) => literals,
+            Capture::Number(_) | Capture::Vocabulary(_) | Capture::Command(_) => &[],
+        }
+    }
+}
+
+impl Capture {
+    /// Like its value, but literals have to be the whole input instead of a part of it
+    /// (case aside), so global commands don't take over inputs that merely contain them.
+    pub fn matches_whole(&self, state: &RuntimeState) -> bool {
+        match &self {
+            Capture::Literals(literals) => {
+                let input = state.last_in.trim();
+                literals.iter().any(|lit| lit.eq_ignore_ascii_case(input))
+            }
+            _ => self.value(state),
         }
     }
 }
 
 impl Condition for Capture {
     fn value(&self, state: &RuntimeState) -> bool {
-        self.captures(&state.last_in)
+        let input = &state.last_in;
+        match &self {
+            Capture::Literals(literals) => literals.iter().any(|lit| input.contains(lit)),
+            Capture::Number(number) => number.captures(input),
+            // Only when it wasn't compiled, there's no word to match it with then.
+            Capture::Vocabulary(_) => false,
+            Capture::Command(command) => state
+                .last_command
+                .as_ref()
+                .is_some_and(|last| command.captures(last)),
+        }
+    }
+
+    fn bind(&self, state: &mut RuntimeState) -> EvalexprResult<()> {
+        match &self {
+            Capture::Literals(_) | Capture::Command(_) => Ok(()),
+            Capture::Vocabulary(name) => Err(EvalexprError::CustomMessage(format!(
+                "The vocabulary \"{}\" wasn't loaded",
+                name
+            ))),
+            Capture::Number(number) => match number.parse(&state.last_in) {
+                Some(value) => state.set_var(&number.var, value),
+                None => Ok(()),
+            },
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match &self {
+            Capture::Literals(literals) => literals.first().map(|_| literals.join("/")),
+            Capture::Number(number) => Some(format!("<{}>", number.var)),
+            Capture::Vocabulary(name) => Some(format!("<{}>", name)),
+            Capture::Command(command) => Some(command.hint()),
+        }
+    }
+}
+
+impl Compiled for Capture {
+    fn compile(&mut self, init: &mut InitializerData, _base: &Path) -> Checked {
+        match self {
+            Capture::Vocabulary(name) => {
+                let words = init
+                    .vocabulary
+                    .get()
+                    .get(name)
+                    .ok_or_else(|| CompileError::Undefined("vocabulary", name.to_owned()))?;
+                *self = Capture::Literals(words.clone());
+                Ok(())
+            }
+            Capture::Command(command) => {
+                command.normalize();
+                init.used_commands.push(command.clone());
+                Ok(())
+            }
+            Capture::Literals(_) | Capture::Number(_) => Ok(()),
+        }
+    }
+}
+
+lazy_static! {
+    static ref NUMBER: Regex = Regex::new(r"-?\d+(?:\.\d+)?").unwrap();
+}
+
+/// Captures the first number in the input, if it passes every comparison given.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberCapture {
+    #[serde(alias = "ge")]
+    pub min: Option<f64>,
+    #[serde(alias = "le")]
+    pub max: Option<f64>,
+    #[serde(alias = "gt")]
+    pub above: Option<f64>,
+    #[serde(alias = "lt")]
+    pub below: Option<f64>,
+    #[serde(alias = "eq")]
+    #[serde(alias = "is")]
+    pub equals: Option<f64>,
+    /// The variable the parsed number is stored in.
+    #[serde(default = "NumberCapture::default_var")]
+    pub var: String,
+    /// Only captures whole numbers and stores them as integers, otherwise every number is a float.
+    #[serde(default)]
+    pub integer: bool,
+}
+
+impl NumberCapture {
+    fn default_var() -> String {
+        "number".to_string()
+    }
+
+    /// Always the same type, as a variable can't change its type once set.
+    pub fn parse(&self, input: &str) -> Option<Value> {
+        let number = NUMBER.find(input)?.as_str();
+        if self.integer {
+            number.parse().ok().map(Value::Int)
+        } else {
+            number.parse().ok().map(Value::Float)
+        }
     }
-}
\ No newline at end of file
+
+    pub fn captures(&self, input: &str) -> bool {
+        let n = match self.parse(input).and_then(|value| value.as_number().ok()) {
+            Some(n) => n,
+            None => return false,
+        };
+        self.min.is_none_or(|min| n >= min)
+            && self.max.is_none_or(|max| n <= max)
+            && self.above.is_none_or(|above| n > above)
+            && self.below.is_none_or(|below| n < below)
+            && self.equals.is_none_or(|equals| n == equals)
+    }
+}
+
+/// Captures a command parsed with the project's lexicon, every part given has to match.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandCapture {
+    pub verb: Option<String>,
+    pub object: Option<String>,
+    /// Adjectives the object has to be described with.
+    #[serde(alias = "adjective")]
+    #[serde(default)]
+    #[serde(deserialize_with = "Many::deserialize_many")]
+    pub adjectives: Vec<String>,
+    pub preposition: Option<String>,
+    pub indirect: Option<String>,
+}
+
+impl CommandCapture {
+    pub fn captures(&self, command: &Command) -> bool {
+        fn noun_is(noun: &Option<Noun>, name: &Option<String>) -> bool {
+            name.as_ref()
+                .is_none_or(|name| noun.as_ref().is_some_and(|noun| &noun.name == name))
+        }
+
+        self.verb.as_ref().is_none_or(|verb| &command.verb == verb)
+            && noun_is(&command.object, &self.object)
+            && self.adjectives.iter().all(|adjective| {
+                command
+                    .object
+                    .as_ref()
+                    .is_some_and(|object| object.adjectives.contains(adjective))
+            })
+            && self
+                .preposition
+                .as_ref()
+                .is_none_or(|preposition| command.preposition.as_ref() == Some(preposition))
+            && noun_is(&command.indirect, &self.indirect)
+    }
+
+    fn hint(&self) -> String {
+        let words: Vec<&str> = (self.verb.iter())
+            .chain(self.adjectives.iter())
+            .chain(self.object.iter())
+            .chain(self.preposition.iter())
+            .chain(self.indirect.iter())
+            .map(String::as_str)
+            .collect();
+        words.join(" ")
+    }
+
+    /// Lowercases the words the input's words are compared with, like the lexicon's.
+    fn normalize(&mut self) {
+        for word in self.adjectives.iter_mut().chain(self.preposition.as_mut()) {
+            *word = word.to_lowercase();
+        }
+    }
+
+    /// Makes sure every word is in the lexicon, so a typo can't make a case impossible to reach.
+    pub fn check(&self, lexicon: &Lexicon) -> Checked {
+        let nouns = [&self.object, &self.indirect].into_iter().flatten();
+        let mut words = (self.verb.iter())
+            .map(|verb| ("verb", verb, lexicon.verbs.contains_key(verb)))
+            .chain(nouns.map(|noun| ("noun", noun, lexicon.nouns.contains_key(noun))))
+            .chain((self.adjectives.iter()).map(|a| ("adjective", a, lexicon.adjectives.contains(a))))
+            .chain((self.preposition.iter()).map(|p| ("preposition", p, lexicon.prepositions.contains(p))));
+        match words.find(|(_, _, known)| !known) {
+            Some((kind, word, _)) => Err(CompileError::Undefined(kind, word.to_owned())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(yaml: &str) -> NumberCapture {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn numbers_keep_their_type() {
+        let any = number("{}");
+        assert_eq!(any.parse("give me 2"), Some(Value::Float(2.0)));
+        assert_eq!(any.parse("give me 2.5"), Some(Value::Float(2.5)));
+        let integer = number("integer: true");
+        assert_eq!(integer.parse("give me 2"), Some(Value::Int(2)));
+        assert_eq!(integer.parse("give me 2.5"), None);
+        assert!(!integer.captures("2.5"));
+    }
+
+    #[test]
+    fn whole_literals() {
+        let capture: Capture = serde_yaml::from_str("!lit [i, help]").unwrap();
+        let mut state = RuntimeState::default();
+        for (input, whole) in [
+            (" I ", true),
+            ("Help", true),
+            ("hint", false),
+            ("take the key", false),
+        ] {
+            state.set_input(input.to_string(), &Lexicon::default());
+            assert_eq!(capture.matches_whole(&state), whole, "{}", input);
+        }
+        state.set_input("helpful".to_string(), &Lexicon::default());
+        assert!(capture.value(&state));
+    }
+
+    #[test]
+    fn comparisons() {
+        let capture = number("{min: 1, lt: 10}");
+        assert!(capture.captures("1"));
+        assert!(capture.captures("about 9.5"));
+        assert!(!capture.captures("10"));
+        assert!(!capture.captures("-3"));
+        assert!(!capture.captures("none"));
+    }
+}
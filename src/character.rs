@@ -1,9 +1,10 @@
-
 use cursive::theme::{Effect, Style};
 use cursive::utils::markup::StyledString;
 use serde::Deserialize;
 
 use crate::character_style::CharacterStyle;
+use crate::settings::Settings;
+use crate::time_span::TimeSpan;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Character {
@@ -12,8 +13,14 @@ pub struct Character {
     #[serde(flatten)]
     pub style: CharacterStyle,
 
-    #[serde(default = "Character::default_duration")]
-    pub duration: u64,
+    /// Typing speed, in characters per second.
+    #[serde(alias = "duration")]
+    #[serde(default = "Character::default_speed")]
+    pub speed: u64,
+
+    /// Overrides the reading delay from the settings for this character's lines.
+    #[serde(default)]
+    pub reading_delay: Option<TimeSpan>,
 }
 
 impl Default for Character {
@@ -21,7 +28,8 @@ impl Default for Character {
         Character {
             name: String::from("__default__"),
             style: CharacterStyle::default(),
-            duration: Character::default_duration(),
+            speed: Character::default_speed(),
+            reading_delay: None,
         }
     }
 }
@@ -51,7 +59,11 @@ impl Character {
     }
     
 
-    fn default_duration() -> u64 {
+    pub fn reading_delay(&self, settings: &Settings) -> TimeSpan {
+        self.reading_delay.unwrap_or(settings.reading_delay)
+    }
+
+    fn default_speed() -> u64 {
         20
     }
 }
@@ -0,0 +1,161 @@
+use cursive::theme::Color;
+use serde::{de, Deserialize, Deserializer};
+
+/// How many colors images are drawn with, `none` keeps them in the terminal's own colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    #[default]
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// The usual colors of the 16 basic ones, terminals are free to pick their own.
+    const ANSI: [[u8; 3]; 16] = [
+        [0, 0, 0],
+        [128, 0, 0],
+        [0, 128, 0],
+        [128, 128, 0],
+        [0, 0, 128],
+        [128, 0, 128],
+        [0, 128, 128],
+        [192, 192, 192],
+        [128, 128, 128],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [0, 0, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+
+    const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// The closest color there is at this depth, `None` when there are no colors.
+    pub fn quantize(self, rgb: [u8; 3]) -> Option<Color> {
+        match self {
+            ColorDepth::None => None,
+            ColorDepth::Ansi16 => {
+                let index = Self::closest(rgb, Self::ANSI.iter().copied());
+                Some(Color::from_256colors(index as u8))
+            }
+            ColorDepth::Ansi256 => Some(Color::from_256colors(Self::index_256(rgb))),
+            ColorDepth::TrueColor => Some(Color::Rgb(rgb[0], rgb[1], rgb[2])),
+        }
+    }
+
+    /// Picks between the 6x6x6 color cube and the 24 grays, leaving out the basic 16.
+    fn index_256(rgb: [u8; 3]) -> u8 {
+        let level = |c: u8| Self::closest([c, c, c], Self::CUBE.iter().map(|&l| [l, l, l]));
+        let cube = rgb.map(level);
+        let cube_rgb = cube.map(|l| Self::CUBE[l]);
+        let average = rgb.iter().map(|&c| c as u32).sum::<u32>() / 3;
+        let gray = (average.saturating_sub(3) / 10).min(23) as u8;
+        let gray_level = 8 + gray * 10;
+        if Self::distance(rgb, [gray_level; 3]) < Self::distance(rgb, cube_rgb) {
+            232 + gray
+        } else {
+            16 + (cube[0] * 36 + cube[1] * 6 + cube[2]) as u8
+        }
+    }
+
+    fn closest(rgb: [u8; 3], colors: impl Iterator<Item = [u8; 3]>) -> usize {
+        colors
+            .enumerate()
+            .min_by_key(|(_, color)| Self::distance(rgb, *color))
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+        a.iter()
+            .zip(b)
+            .map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32)
+            .sum()
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorDepth {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Count(u32),
+            Name(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Count(16) => Ok(ColorDepth::Ansi16),
+            Raw::Count(256) => Ok(ColorDepth::Ansi256),
+            Raw::Name(name) => match name.as_str() {
+                "none" | "off" => Ok(ColorDepth::None),
+                "16" => Ok(ColorDepth::Ansi16),
+                "256" => Ok(ColorDepth::Ansi256),
+                "truecolor" | "trueColor" | "rgb" => Ok(ColorDepth::TrueColor),
+                _ => Err(de::Error::custom(format!("unknown color depth \"{}\"", name))),
+            },
+            Raw::Count(count) => Err(de::Error::custom(format!(
+                "no color depth with {} colors (use 16, 256 or truecolor)",
+                count
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cursive::theme::BaseColor;
+
+    use super::*;
+
+    #[test]
+    fn without_colors() {
+        assert_eq!(ColorDepth::None.quantize([200, 10, 10]), None);
+    }
+
+    #[test]
+    fn true_color_is_kept() {
+        assert_eq!(
+            ColorDepth::TrueColor.quantize([1, 2, 3]),
+            Some(Color::Rgb(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn closest_of_the_basic_colors() {
+        let ansi = |rgb| ColorDepth::Ansi16.quantize(rgb);
+        assert_eq!(ansi([250, 10, 10]), Some(Color::Light(BaseColor::Red)));
+        assert_eq!(ansi([120, 0, 10]), Some(Color::Dark(BaseColor::Red)));
+        assert_eq!(ansi([10, 10, 10]), Some(Color::Dark(BaseColor::Black)));
+        assert_eq!(ansi([240, 240, 240]), Some(Color::Light(BaseColor::White)));
+    }
+
+    #[test]
+    fn cube_or_gray() {
+        assert_eq!(ColorDepth::index_256([255, 0, 0]), 16 + 5 * 36);
+        assert_eq!(ColorDepth::index_256([0, 95, 255]), 16 + 6 + 5);
+        assert_eq!(ColorDepth::index_256([128, 128, 128]), 232 + 12);
+        assert_eq!(ColorDepth::index_256([0, 0, 0]), 16);
+        assert_eq!(ColorDepth::index_256([255, 255, 255]), 16 + 215);
+    }
+
+    #[test]
+    fn depths_from_yaml() {
+        let depths: Vec<ColorDepth> = serde_yaml::from_str("[16, '256', none, rgb]").unwrap();
+        assert_eq!(
+            depths,
+            [
+                ColorDepth::Ansi16,
+                ColorDepth::Ansi256,
+                ColorDepth::None,
+                ColorDepth::TrueColor
+            ]
+        );
+        assert!(serde_yaml::from_str::<ColorDepth>("8").is_err());
+    }
+}
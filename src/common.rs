@@ -28,9 +28,6 @@ impl<T> Many<T> where T: DeserializeOwned {
     }
 }
 
-pub fn sleep(seconds: u64) {
-    use std::thread::sleep;
-    use std::time::Duration;
-
-    sleep(Duration::from_secs(seconds));
+pub fn sleep(duration: std::time::Duration) {
+    std::thread::sleep(duration);
 }
\ No newline at end of file
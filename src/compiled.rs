@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use image::ImageError;
 
@@ -9,6 +9,8 @@ pub enum CompileError {
     IO(std::io::Error),
     InvalidPath(PathBuf),
     Image(ImageError),
+    /// Something referred to by name that was never defined, like `("vocabulary", "yes")`.
+    Undefined(&'static str, String),
 }
 
 impl CompileError {
@@ -18,6 +20,7 @@ impl CompileError {
             Self::IO(_) => "IO".to_owned(),
             Self::InvalidPath(_) => "UnvalidPath".to_owned(),
             Self::Image(_) => "Image".to_owned(),
+            Self::Undefined(..) => "Undefined".to_owned(),
         }
     }
 }
@@ -40,6 +43,7 @@ impl From<ImageError> for CompileError {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for CompileError {
     #[inline]
     fn to_string(&self) -> String {
@@ -48,6 +52,7 @@ impl ToString for CompileError {
             Self::IO(e) => e.to_string(),
             Self::InvalidPath(buf) => "Unvalid Path: ".to_string() + &format!("{:?}", buf) + ".",
             Self::Image(e) => e.to_string(),
+            Self::Undefined(kind, name) => format!("Undefined {} \"{}\".", kind, name),
         }
     }
 }
@@ -56,5 +61,5 @@ pub type Comp<T> = Result<T, CompileError>;
 pub type Checked = Comp<()>;
 
 pub trait Compiled {
-    fn compile(&mut self, init: &mut InitializerData, base: &PathBuf) -> Checked;
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked;
 }
@@ -1,13 +1,26 @@
 use std::fmt::Debug;
+use std::path::Path;
 
+use evalexpr::EvalexprResult;
 use serde::Deserialize;
 
 use crate::capture::Capture;
-use crate::initializer::RuntimeState;
+use crate::compiled::{Checked, Compiled};
+use crate::initializer::{InitializerData, RuntimeState};
 
 pub trait Condition {
     // TODO: Rename this function...
     fn value(&self, state: &RuntimeState) -> bool;
+
+    /// Sets the variables a condition exposes to the section it guards.
+    fn bind(&self, _state: &mut RuntimeState) -> EvalexprResult<()> {
+        Ok(())
+    }
+
+    /// What the player could type to satisfy it, if it can be put into words.
+    fn hint(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,12 +32,62 @@ pub enum Conditional {
     LastIn(Vec<Capture>),
 }
 
+impl Compiled for Conditional {
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked {
+        match self {
+            Conditional::Expression(_) => Ok(()),
+            Conditional::LastIn(captures) => {
+                for capture in captures.iter_mut() {
+                    capture.compile(init, base)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 impl Condition for Conditional {
     fn value(&self, state: &RuntimeState) -> bool {
         match &self {
             Conditional::Expression(expr) => state.var_condition(expr),
-            Conditional::LastIn(captures) => captures.iter().all(|cap| cap.captures(&state.last_in)),
+            Conditional::LastIn(captures) => captures.iter().all(|cap| cap.value(state)),
+        }
+    }
+
+    fn bind(&self, state: &mut RuntimeState) -> EvalexprResult<()> {
+        match &self {
+            Conditional::Expression(_) => Ok(()),
+            Conditional::LastIn(captures) => captures.iter().try_for_each(|cap| cap.bind(state)),
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match &self {
+            Conditional::Expression(_) => None,
+            Conditional::LastIn(captures) => {
+                let hints: Option<Vec<String>> = captures.iter().map(Condition::hint).collect();
+                hints.map(|hints| hints.join(" "))
+            }
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use evalexpr::{Context, Value};
+
+    use super::*;
+    use crate::parser::Lexicon;
+
+    #[test]
+    fn last_in_binds_its_captures() {
+        let conditional: Conditional =
+            serde_yaml::from_str("!lastIn [!num {var: n}, !lit [apples]]").unwrap();
+        let mut state = RuntimeState::default();
+        state.set_input("3 apples".to_string(), &Lexicon::default());
+        assert!(conditional.value(&state));
+        conditional.bind(&mut state).unwrap();
+        assert_eq!(state.context.get_value("n"), Some(&Value::Float(3.0)));
+        assert_eq!(conditional.hint().as_deref(), Some("<n> apples"));
+    }
+}
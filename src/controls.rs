@@ -0,0 +1,170 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+use cursive::event::{Event, EventTrigger, Key, MouseButton, MouseEvent};
+use cursive::CursiveRunnable;
+use serde::Deserialize;
+
+/// How the story moves on after a dialog line was written.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AdvanceMode {
+    /// Straight to the next section.
+    #[default]
+    #[serde(alias = "none")]
+    Off,
+    /// Waits for the player to press advance.
+    Click,
+    /// Waits for the time it takes to read the line, or for advance if it's pressed first.
+    Auto,
+}
+
+impl AdvanceMode {
+    fn toggled(self) -> Self {
+        match self {
+            AdvanceMode::Off | AdvanceMode::Auto => AdvanceMode::Click,
+            AdvanceMode::Click => AdvanceMode::Auto,
+        }
+    }
+}
+
+impl From<u8> for AdvanceMode {
+    fn from(mode: u8) -> Self {
+        match mode {
+            1 => AdvanceMode::Click,
+            2 => AdvanceMode::Auto,
+            _ => AdvanceMode::Off,
+        }
+    }
+}
+
+impl From<AdvanceMode> for u8 {
+    fn from(mode: AdvanceMode) -> Self {
+        match mode {
+            AdvanceMode::Off => 0,
+            AdvanceMode::Click => 1,
+            AdvanceMode::Auto => 2,
+        }
+    }
+}
+
+/// Player controls, pressed on the cursive thread and polled by the thread executing the sections.
+#[derive(Clone, Default)]
+pub struct Controls {
+    inner: Arc<ControlsState>,
+}
+
+#[derive(Default)]
+struct ControlsState {
+    listening: AtomicBool,
+    advance: AtomicBool,
+    fast_forward: Mutex<Option<Instant>>,
+    skip_read: AtomicBool,
+    advance_mode: AtomicU8,
+}
+
+impl Controls {
+    pub const FAST_FORWARD_KEY: char = 'f';
+    pub const SKIP_READ_KEY: char = 's';
+    pub const ADVANCE_MODE_KEY: char = 'a';
+
+    /// How many times faster text is written while fast-forwarding.
+    pub const FAST_FORWARD_SPEED: f32 = 10.0;
+
+    // Terminals don't report key releases, so a fast-forward press lasts until the
+    // key repeats again (which happens constantly while it's held down).
+    const FAST_FORWARD_HOLD: Duration = Duration::from_millis(500);
+
+    pub fn register(&self, siv: &mut CursiveRunnable) {
+        let advance = || {
+            let controls = self.clone();
+            move |_: &mut cursive::Cursive| controls.press_advance()
+        };
+        siv.add_global_callback(Event::Key(Key::Enter), advance());
+        siv.add_global_callback(' ', advance());
+        siv.set_on_post_event(
+            EventTrigger::from_fn(|e| {
+                matches!(
+                    e,
+                    Event::Mouse {
+                        event: MouseEvent::Press(MouseButton::Left),
+                        ..
+                    }
+                )
+            }),
+            advance(),
+        );
+
+        let controls = self.clone();
+        siv.add_global_callback(Self::FAST_FORWARD_KEY, move |_| {
+            controls.press_fast_forward()
+        });
+        let controls = self.clone();
+        siv.add_global_callback(Self::SKIP_READ_KEY, move |_| {
+            controls.toggle_skip_read();
+        });
+        let controls = self.clone();
+        siv.add_global_callback(Self::ADVANCE_MODE_KEY, move |_| {
+            controls.set_advance_mode(controls.advance_mode().toggled())
+        });
+    }
+
+    /// Starts accepting advance presses, presses made before this are dropped.
+    pub fn listen(&self) {
+        self.inner.listening.store(true, Ordering::SeqCst);
+    }
+
+    /// Stops accepting advance presses and forgets the pending one.
+    pub fn reset(&self) {
+        self.inner.listening.store(false, Ordering::SeqCst);
+        self.inner.advance.store(false, Ordering::SeqCst);
+    }
+
+    pub fn press_advance(&self) {
+        if self.inner.listening.load(Ordering::SeqCst) {
+            self.inner.advance.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn advance_pressed(&self) -> bool {
+        self.inner.advance.load(Ordering::SeqCst)
+    }
+
+    pub fn press_fast_forward(&self) {
+        *self.inner.fast_forward.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn fast_forwarding(&self) -> bool {
+        self.inner
+            .fast_forward
+            .lock()
+            .unwrap()
+            .is_some_and(|pressed| pressed.elapsed() < Self::FAST_FORWARD_HOLD)
+    }
+
+    /// The speed multiplier text should currently be written in.
+    pub fn speed(&self) -> f32 {
+        if self.fast_forwarding() {
+            Self::FAST_FORWARD_SPEED
+        } else {
+            1.0
+        }
+    }
+
+    pub fn toggle_skip_read(&self) -> bool {
+        !self.inner.skip_read.fetch_xor(true, Ordering::SeqCst)
+    }
+
+    pub fn skip_read(&self) -> bool {
+        self.inner.skip_read.load(Ordering::SeqCst)
+    }
+
+    pub fn advance_mode(&self) -> AdvanceMode {
+        AdvanceMode::from(self.inner.advance_mode.load(Ordering::SeqCst))
+    }
+
+    pub fn set_advance_mode(&self, mode: AdvanceMode) {
+        self.inner.advance_mode.store(mode.into(), Ordering::SeqCst);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::capture::Capture;
 use crate::initializer::{InitializerData, RuntimeState};
+use crate::switcher::Case;
 use crate::ui_messenger::UIMessenger;
 
 // TODO: Find a better way to house all of these...
@@ -8,6 +10,35 @@ pub struct ExecutionState<'a> {
     pub ui: UIMessenger,
 }
 
+impl ExecutionState<'_> {
+    /// Lets Tab complete the literals of the cases and global commands.
+    pub fn offer_completions<'c>(&self, cases: impl IntoIterator<Item = &'c Case<Capture>>) {
+        fn literals(case: &Case<Capture>) -> impl Iterator<Item = String> + '_ {
+            (case.captures.iter()).flat_map(|capture| capture.literals().iter().cloned())
+        }
+
+        let completions = (cases.into_iter().flat_map(literals))
+            .chain(self.init.commands.iter().flat_map(literals))
+            .collect();
+        self.ui.set_completions(completions);
+    }
+
+    /// Runs the first global command capturing the whole last input, returns `false` if there's none.
+    pub fn run_global_command(&mut self) -> bool {
+        let init = self.init;
+        let matches = |command: &&Case<Capture>| {
+            (command.captures.iter()).all(|capture| capture.matches_whole(self.state))
+        };
+        match init.commands.iter().find(matches) {
+            Some(command) => {
+                command.execute(self);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 pub trait Executable {
     fn execute(&self, execution: &mut ExecutionState);
 }
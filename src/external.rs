@@ -0,0 +1,50 @@
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::compiled::Checked;
+use crate::file_format::FileFormat;
+use crate::path_reference::PathReference;
+
+/// Data written either in place, or in a file of its own referred to without its extension.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum External<T> {
+    Inline(T),
+    File(PathReference),
+}
+
+impl<T: Default> Default for External<T> {
+    fn default() -> Self {
+        External::Inline(T::default())
+    }
+}
+
+impl<T: DeserializeOwned> External<T> {
+    /// Reads the file, if there is one.
+    pub fn load(&mut self, extension: &FileFormat, base: &Path) -> Checked {
+        if let External::File(file) = self {
+            let mut path = file.logical_path(base);
+            path.set_extension(extension.name());
+            let raw_contents = fs::read_to_string(&path)?;
+            *self = External::Inline(extension.deserialize_str(&raw_contents)?);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self) -> &T {
+        match self {
+            External::Inline(data) => data,
+            External::File(_) => unreachable!("external data is loaded before compiling"),
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        match self {
+            External::Inline(data) => data,
+            External::File(_) => unreachable!("external data is loaded before compiling"),
+        }
+    }
+}
@@ -4,6 +4,7 @@ use serde_yaml::from_str as yaml_from_str;
 
 #[derive(Debug)]
 pub enum FileFormat {
+    #[allow(dead_code)]
     Json,
     Yaml,
 }
@@ -27,12 +28,14 @@ impl FileFormat {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for FileFormat {
     fn default() -> Self {
         FileFormat::Yaml
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 pub enum FormatError {
     YAML(serde_yaml::Error),
     JSON(serde_json::Error),
@@ -59,6 +62,7 @@ impl From<serde_yaml::Error> for FormatError {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for FormatError {
     #[inline]
     fn to_string(&self) -> String {
@@ -0,0 +1,158 @@
+use cursive::theme::{Color, ColorStyle, ColorType, Style};
+use cursive::utils::markup::StyledString;
+use unicode_width::UnicodeWidthChar;
+
+/// A character on the frame, colors left out use the terminal's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+impl Cell {
+    pub const BLANK: Cell = Cell::plain(' ');
+    /// Stands in for the second column of the wide glyph before it.
+    pub const PLACEHOLDER: char = '\0';
+
+    pub const fn plain(ch: char) -> Self {
+        Cell {
+            ch,
+            fg: None,
+            bg: None,
+        }
+    }
+
+    /// Whether what's under it shows through when it's on a layer.
+    pub fn is_transparent(&self) -> bool {
+        self.ch == ' ' && self.bg.is_none()
+    }
+
+    pub fn is_placeholder(&self) -> bool {
+        self.ch == Cell::PLACEHOLDER
+    }
+
+    fn is_wide(&self) -> bool {
+        self.ch.width() == Some(2)
+    }
+
+    fn style(&self) -> Style {
+        let color = |color: Option<Color>| color.map_or(ColorType::InheritParent, ColorType::Color);
+        Style::from(ColorStyle::new(color(self.fg), color(self.bg)))
+    }
+}
+
+/// A grid of cells to draw on the frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    /// One cell a column, so wide glyphs are followed by a placeholder and characters
+    /// without a width of their own (like combining marks) are left out.
+    pub fn from_text(text: &str) -> Self {
+        let row = |line: &str| {
+            let mut cells = Vec::new();
+            for ch in line.chars() {
+                match ch.width() {
+                    Some(2) => cells.extend([Cell::plain(ch), Cell::plain(Cell::PLACEHOLDER)]),
+                    Some(1) => cells.push(Cell::plain(ch)),
+                    _ => {}
+                }
+            }
+            cells
+        };
+        Frame {
+            rows: text.lines().map(row).collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// The cell in the column and row, blank past the edges.
+    pub fn cell(&self, x: usize, y: usize) -> Cell {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(Cell::BLANK)
+    }
+
+    /// Draws `frame` over this one, `x` columns right and `y` rows down, leaving out its transparent cells.
+    pub fn draw(&mut self, frame: &Frame, x: usize, y: usize) {
+        for (row_index, row) in frame.rows.iter().enumerate() {
+            let Some(last) = row.iter().rposition(|cell| !cell.is_transparent()) else {
+                continue;
+            };
+            if self.rows.len() <= y + row_index {
+                self.rows.resize(y + row_index + 1, Vec::new());
+            }
+            let target = &mut self.rows[y + row_index];
+            if target.len() <= x + last {
+                target.resize(x + last + 1, Cell::BLANK);
+            }
+            for (column, cell) in row[..=last].iter().enumerate() {
+                if !cell.is_transparent() {
+                    target[x + column] = *cell;
+                }
+            }
+        }
+    }
+
+    /// Cells next to each other with the same colors share a span.
+    pub fn to_styled(&self) -> StyledString {
+        let mut styled = StyledString::new();
+        for row in self.rows.iter() {
+            let row = Self::paired(row);
+            for run in row.chunk_by(|a, b| (a.fg, a.bg) == (b.fg, b.bg)) {
+                let text: String = (run.iter())
+                    .filter(|cell| !cell.is_placeholder())
+                    .map(|cell| cell.ch)
+                    .collect();
+                if !text.is_empty() {
+                    styled.append_styled(text, run[0].style());
+                }
+            }
+            styled.append_plain("\n");
+        }
+        styled
+    }
+
+    /// Blanks the halves of wide glyphs split apart by a layer or a transition,
+    /// so every later cell stays in its column.
+    fn paired(row: &[Cell]) -> Vec<Cell> {
+        let mut row = row.to_vec();
+        for x in 0..row.len() {
+            let broken = if row[x].is_placeholder() {
+                x == 0 || !row[x - 1].is_wide()
+            } else {
+                row[x].is_wide() && !row.get(x + 1).is_some_and(Cell::is_placeholder)
+            };
+            if broken {
+                row[x].ch = ' ';
+            }
+        }
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_glyphs_take_two_columns() {
+        let mut frame = Frame::from_text("猫a\ne\u{301}🙂");
+        assert_eq!(frame.rows[0].len(), 3);
+        assert_eq!(frame.width(), 3);
+        assert_eq!(frame.cell(2, 0).ch, 'a');
+        assert_eq!(frame.to_styled().source(), "猫a\ne🙂\n");
+
+        frame.draw(&Frame::from_text("b"), 1, 0);
+        frame.draw(&Frame::from_text("c"), 1, 1);
+        assert_eq!(frame.to_styled().source(), " ba\nec \n");
+    }
+}
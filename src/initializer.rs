@@ -1,21 +1,30 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use evalexpr::{
-    ContextWithMutableVariables, eval_boolean_with_context, eval_with_context,
-    eval_with_context_mut, HashMapContext, Value,
+    ContextWithMutableFunctions, ContextWithMutableVariables, eval_boolean_with_context, eval_with_context,
+    eval_with_context_mut, EvalexprResult, Function, HashMapContext, Value,
 };
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Deserializer};
 
+use crate::capture::{Capture, CommandCapture};
 use crate::character::Character;
 use crate::compiled::{Comp, Compiled, CompileError};
 use crate::executable::{Executable, ExecutionState};
+use crate::external::External;
 use crate::file_format::FileFormat;
+use crate::inventory::{Inventory, Item};
+use crate::parser::{Command, Lexicon};
 use crate::section::Section;
+use crate::settings::Settings;
+use crate::switcher::Case;
 use crate::ui_messenger::UIMessenger;
+use crate::vocabulary::Vocabulary;
+use crate::world::WorldState;
 
 #[derive(Debug, Deserialize)]
 pub struct InitializerData {
@@ -29,6 +38,33 @@ pub struct InitializerData {
     pub characters: HashMap<String, Character>,
     #[serde(default)]
     pub default_character: Character,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(deserialize_with = "deserialize_items")]
+    #[serde(default)]
+    pub items: HashMap<String, Item>,
+    #[serde(alias = "vocab")]
+    #[serde(default)]
+    pub vocabulary: External<Vocabulary>,
+    #[serde(alias = "parser")]
+    #[serde(default)]
+    pub lexicon: External<Lexicon>,
+    /// How many worlds were compiled, each one's key is the count before it.
+    #[serde(skip_deserializing)]
+    #[serde(skip_serializing)]
+    pub worlds: usize,
+    /// Given or taken by sections, checked once everything is compiled since worlds add their objects as items.
+    #[serde(skip_deserializing)]
+    #[serde(skip_serializing)]
+    pub used_items: Vec<String>,
+    /// Captured by cases, checked against the lexicon once worlds have added their words to it.
+    #[serde(skip_deserializing)]
+    #[serde(skip_serializing)]
+    pub used_commands: Vec<CommandCapture>,
+    /// Moved here from the initializer once compiled.
+    #[serde(skip_deserializing)]
+    #[serde(skip_serializing)]
+    pub commands: Vec<Case<Capture>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -39,6 +75,10 @@ pub struct Initializer {
     #[serde(flatten)]
     data: InitializerData,
     entry: Section,
+    /// Checked at every prompt before its own cases, which are asked again afterwards.
+    // Not part of the data since flattened fields can't hold tagged sections.
+    #[serde(default)]
+    commands: Vec<Case<Capture>>,
     #[serde(skip_deserializing)]
     #[serde(skip_serializing)]
     state: RuntimeState,
@@ -48,7 +88,7 @@ impl Initializer {
     pub fn new(root: String, extension: FileFormat) -> Comp<Self> {
         let mut path = PathBuf::from(&root);
         path.push("init");
-        path.set_extension(&extension.name());
+        path.set_extension(extension.name());
         let filename = path.to_str();
         if filename.is_none() {
             return Err(CompileError::InvalidPath(path.to_path_buf()));
@@ -62,12 +102,34 @@ impl Initializer {
 
         initializer.data.extension = extension;
         initializer.root = path.to_owned();
+        initializer
+            .data
+            .vocabulary
+            .load(&initializer.data.extension, &path)?;
+        initializer
+            .data
+            .lexicon
+            .load(&initializer.data.extension, &path)?;
+        initializer.data.lexicon.get_mut().normalize();
         initializer.entry.compile(&mut initializer.data, &path)?;
+        for command in initializer.commands.iter_mut() {
+            command.compile(&mut initializer.data, &path)?;
+        }
+        initializer.data.commands = std::mem::take(&mut initializer.commands);
+        let data = &initializer.data;
+        if let Some(item) = (data.used_items.iter()).find(|item| !data.items.contains_key(*item)) {
+            return Err(CompileError::Undefined("item", item.to_owned()));
+        }
+        for command in data.used_commands.iter() {
+            command.check(data.lexicon.get())?;
+        }
 
         Ok(initializer)
     }
 
-    pub fn execute(&mut self, ui: UIMessenger) {
+    pub fn execute(&mut self, mut ui: UIMessenger) {
+        ui.configure(&self.data.settings);
+        self.state.update_inventory_functions();
         self.entry.execute(&mut ExecutionState {
             init: &self.data,
             state: &mut self.state,
@@ -85,10 +147,23 @@ where
     Ok(map)
 }
 
+fn deserialize_items<'de, D>(deserializer: D) -> Result<HashMap<String, Item>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let vec: Vec<Item> = Vec::deserialize(deserializer)?;
+    let map: HashMap<_, _> = vec.into_iter().map(|i| (i.id.clone(), i)).collect();
+    Ok(map)
+}
+
 #[derive(Debug, Default)]
 pub struct RuntimeState {
     pub last_in: String,
+    /// `last_in` parsed with the project's lexicon.
+    pub last_command: Option<Command>,
     pub context: HashMapContext,
+    pub inventory: Inventory,
+    pub world: WorldState,
 }
 
 lazy_static! {
@@ -96,15 +171,50 @@ lazy_static! {
 }
 
 impl RuntimeState {
-    pub fn update_input(&mut self, m: &mut UIMessenger) -> &str {
-        self.last_in = m.get_append_input();
+    /// Stores what the player entered, along with the command it parses into.
+    pub fn set_input(&mut self, input: String, lexicon: &Lexicon) {
+        self.last_command = lexicon.parse(&input);
+        self.last_in = input;
         self.context
             .set_value(
                 "last_in".to_string(),
                 Value::String(self.last_in.to_owned()),
             )
             .unwrap();
-        &self.last_in
+    }
+
+    pub fn give(&mut self, item: &str, amount: u32) {
+        self.inventory.add(item, amount);
+        self.update_inventory_functions();
+    }
+
+    /// Returns `false` and takes nothing if the player doesn't have enough.
+    pub fn take(&mut self, item: &str, amount: u32) -> bool {
+        let taken = self.inventory.remove(item, amount);
+        self.update_inventory_functions();
+        taken
+    }
+
+    /// Registers `has("id")` and `count("id")` again, they only see the inventory they were made with.
+    pub fn update_inventory_functions(&mut self) {
+        let inventory = Arc::new(self.inventory.clone());
+        let counted = inventory.clone();
+        self.context
+            .set_function(
+                "has".to_string(),
+                Function::new(move |id| Ok(Value::Boolean(inventory.contains(&id.as_string()?)))),
+            )
+            .unwrap();
+        self.context
+            .set_function(
+                "count".to_string(),
+                Function::new(move |id| Ok(Value::Int(counted.count(&id.as_string()?) as i64))),
+            )
+            .unwrap();
+    }
+
+    pub fn set_var(&mut self, name: &str, value: Value) -> EvalexprResult<()> {
+        self.context.set_value(name.to_string(), value)
     }
 
     pub fn expand(&self, val: &str) -> Value {
@@ -139,3 +249,55 @@ impl RuntimeState {
         eval_boolean_with_context(expr, &self.context).unwrap_or(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(name: &str, init: &str) -> Comp<Initializer> {
+        let root =
+            std::env::temp_dir().join(format!("initializer_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("init.yaml"), init).unwrap();
+        let initializer = Initializer::new(root.to_string_lossy().into_owned(), FileFormat::Yaml);
+        fs::remove_dir_all(root).unwrap();
+        initializer
+    }
+
+    #[test]
+    fn world_words_can_be_used_before_the_world() {
+        let init = "
+            characters: []
+            entry: !seq
+              - !give lamp
+              - !in
+                cases:
+                  - cap: [!cmd {verb: take, object: lamp}]
+                    sec: !print Taken.
+              - !explore
+                start: hall
+                locations: {hall: {name: Hall}}
+                objects: {lamp: {name: lamp, location: hall}}
+            ";
+        assert!(compile("before", init).is_ok());
+    }
+
+    #[test]
+    fn unknown_words_and_items() {
+        let command = "
+            characters: []
+            entry: !in
+              cases:
+                - cap: [!cmd {verb: dance}]
+                  sec: !print Whee.
+            ";
+        let error = compile("command", command).err().unwrap();
+        assert_eq!(error.to_string(), "Undefined verb \"dance\".");
+        let item = "
+            characters: []
+            entry: !give spoon
+            ";
+        let error = compile("item", item).err().unwrap();
+        assert_eq!(error.to_string(), "Undefined item \"spoon\".");
+    }
+}
@@ -0,0 +1,286 @@
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Deserializer};
+
+/// Input history, completions and how the current prompt reads, filled by the thread executing the sections and used by the text input.
+#[derive(Clone, Default)]
+pub struct InputAssist {
+    inner: Arc<Mutex<AssistState>>,
+}
+
+#[derive(Default)]
+struct AssistState {
+    history_enabled: bool,
+    completion_enabled: bool,
+    history: Vec<String>,
+    /// The entry shown while browsing the history, `None` while writing a new line.
+    browsing: Option<usize>,
+    /// The line being written when browsing started, brought back after the newest entry.
+    draft: String,
+    completions: Vec<String>,
+    entry: EntryOptions,
+    /// What was really typed while the input is masked.
+    hidden: String,
+}
+
+impl InputAssist {
+    const HISTORY_SIZE: usize = 100;
+
+    pub fn configure(&self, history: bool, completion: bool) {
+        let mut state = self.inner.lock().unwrap();
+        state.history_enabled = history;
+        state.completion_enabled = completion;
+    }
+
+    pub fn set_completions(&self, completions: Vec<String>) {
+        self.inner.lock().unwrap().completions = completions;
+    }
+
+    pub fn entry(&self) -> EntryOptions {
+        self.inner.lock().unwrap().entry.clone()
+    }
+
+    pub fn set_entry(&self, entry: EntryOptions) {
+        let mut state = self.inner.lock().unwrap();
+        state.entry = entry;
+        state.hidden.clear();
+    }
+
+    /// Adds a character to the masked input, returns what to show instead.
+    pub fn type_hidden(&self, c: char) -> String {
+        let mut state = self.inner.lock().unwrap();
+        state.hidden.push(c);
+        state.entry.masked(&state.hidden)
+    }
+
+    /// Removes the last character of the masked input, returns what to show instead.
+    pub fn erase_hidden(&self) -> String {
+        let mut state = self.inner.lock().unwrap();
+        state.hidden.pop();
+        state.entry.masked(&state.hidden)
+    }
+
+    pub fn hidden_length(&self) -> usize {
+        self.inner.lock().unwrap().hidden.chars().count()
+    }
+
+    pub fn take_hidden(&self) -> String {
+        std::mem::take(&mut self.inner.lock().unwrap().hidden)
+    }
+
+    /// Remembers the input, unless it was masked.
+    pub fn submit(&self, input: &str) {
+        let mut state = self.inner.lock().unwrap();
+        state.browsing = None;
+        if !state.history_enabled
+            || state.entry.mask.is_some()
+            || input.trim().is_empty()
+            || state.history.last().is_some_and(|last| last == input)
+        {
+            return;
+        }
+        if state.history.len() == Self::HISTORY_SIZE {
+            state.history.remove(0);
+        }
+        state.history.push(input.to_string());
+    }
+
+    /// The entry before the one shown, `None` if there's nothing older.
+    pub fn previous(&self, current: &str) -> Option<String> {
+        let mut state = self.inner.lock().unwrap();
+        if !state.history_enabled || state.entry.mask.is_some() || state.history.is_empty() {
+            return None;
+        }
+        let index = match state.browsing {
+            Some(0) => return None,
+            Some(index) => index - 1,
+            None => {
+                state.draft = current.to_string();
+                state.history.len() - 1
+            }
+        };
+        state.browsing = Some(index);
+        Some(state.history[index].to_owned())
+    }
+
+    /// The entry after the one shown, or the line that was being written past the newest one.
+    pub fn next(&self) -> Option<String> {
+        let mut state = self.inner.lock().unwrap();
+        let index = state.browsing? + 1;
+        if index < state.history.len() {
+            state.browsing = Some(index);
+            Some(state.history[index].to_owned())
+        } else {
+            state.browsing = None;
+            Some(std::mem::take(&mut state.draft))
+        }
+    }
+
+    /// Completes the input as far as every completion starting with it agrees.
+    pub fn complete(&self, current: &str) -> Option<String> {
+        let state = self.inner.lock().unwrap();
+        if !state.completion_enabled || state.entry.mask.is_some() || current.is_empty() {
+            return None;
+        }
+        let lower = current.to_lowercase();
+        let mut candidates = state
+            .completions
+            .iter()
+            .filter(|completion| completion.to_lowercase().starts_with(&lower));
+        let first = candidates.next()?;
+        let common = candidates.fold(first.as_str(), |common, candidate| {
+            let len = common
+                .char_indices()
+                .zip(candidate.chars())
+                .find(|((_, a), b)| !a.eq_ignore_ascii_case(b))
+                .map_or(common.len().min(candidate.len()), |((i, _), _)| i);
+            &common[..len]
+        });
+        (common.len() > current.len()).then(|| common.to_string())
+    }
+}
+
+/// How the text input behaves while a prompt reads from it.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryOptions {
+    /// Shown in place of every character typed.
+    pub mask: Option<char>,
+    /// Enter starts a new line, the input is submitted with Ctrl+D.
+    #[serde(default)]
+    pub multiline: bool,
+    /// `digits`, `letters`, `alphanumeric`, or the characters that can be typed.
+    /// Letters and alphanumerics allow spaces and line breaks too.
+    pub filter: Option<CharFilter>,
+    pub max_length: Option<usize>,
+    /// Writes the input into the story once submitted (masked if the input is).
+    #[serde(default = "EntryOptions::default_echo")]
+    pub echo: bool,
+}
+
+impl Default for EntryOptions {
+    fn default() -> Self {
+        EntryOptions {
+            mask: None,
+            multiline: false,
+            filter: None,
+            max_length: None,
+            echo: EntryOptions::default_echo(),
+        }
+    }
+}
+
+impl EntryOptions {
+    fn default_echo() -> bool {
+        true
+    }
+
+    /// Whether `c` can be typed after `length` characters.
+    pub fn accepts(&self, c: char, length: usize) -> bool {
+        self.max_length.is_none_or(|max| length < max)
+            && self.filter.as_ref().is_none_or(|filter| filter.accepts(c))
+    }
+
+    /// Line breaks are kept, so a multi-line input shows its lines.
+    pub fn masked(&self, input: &str) -> String {
+        match self.mask {
+            Some(mask) => (input.chars())
+                .map(|c| if c == '\n' { c } else { mask })
+                .collect(),
+            None => input.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CharFilter {
+    Digits,
+    Letters,
+    Alphanumeric,
+    Only(String),
+}
+
+impl CharFilter {
+    fn accepts(&self, c: char) -> bool {
+        match self {
+            CharFilter::Digits => c.is_ascii_digit(),
+            CharFilter::Letters => c.is_alphabetic() || c == ' ' || c == '\n',
+            CharFilter::Alphanumeric => c.is_alphanumeric() || c == ' ' || c == '\n',
+            CharFilter::Only(chars) => chars.contains(c),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CharFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match String::deserialize(deserializer)?.as_str() {
+            "digits" => CharFilter::Digits,
+            "letters" => CharFilter::Letters,
+            "alphanumeric" => CharFilter::Alphanumeric,
+            chars => CharFilter::Only(chars.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assist(completions: &[&str]) -> InputAssist {
+        let assist = InputAssist::default();
+        assist.configure(false, true);
+        assist.set_completions(completions.iter().map(|c| c.to_string()).collect());
+        assist
+    }
+
+    #[test]
+    fn completes_the_only_match() {
+        let assist = assist(&["open door", "look"]);
+        assert_eq!(assist.complete("op").as_deref(), Some("open door"));
+        assert_eq!(assist.complete("LO").as_deref(), Some("look"));
+    }
+
+    #[test]
+    fn completes_as_far_as_the_matches_agree() {
+        let assist = assist(&["open door", "open box", "Open Window"]);
+        assert_eq!(assist.complete("o").as_deref(), Some("open "));
+        assert_eq!(assist.complete("open b").as_deref(), Some("open box"));
+    }
+
+    #[test]
+    fn nothing_to_complete() {
+        let assist = assist(&["open door", "open box"]);
+        assert_eq!(assist.complete(""), None);
+        assert_eq!(assist.complete("x"), None);
+        assert_eq!(assist.complete("open "), None);
+        assert_eq!(assist.complete("open door"), None);
+    }
+
+    #[test]
+    fn not_while_disabled_or_masked() {
+        let assist = assist(&["open door"]);
+        assist.set_entry(EntryOptions {
+            mask: Some('*'),
+            ..EntryOptions::default()
+        });
+        assert_eq!(assist.complete("op"), None);
+        assist.set_entry(EntryOptions::default());
+        assist.configure(false, false);
+        assert_eq!(assist.complete("op"), None);
+    }
+
+    #[test]
+    fn line_breaks_in_multiline_entries() {
+        let entry: EntryOptions =
+            serde_yaml::from_str("{mask: '*', multiline: true, maxLength: 3, filter: letters}")
+                .unwrap();
+        assert_eq!(entry.masked("ab\nc"), "**\n*");
+        assert!(entry.accepts('\n', 2));
+        assert!(!entry.accepts('\n', 3));
+        let digits: EntryOptions = serde_yaml::from_str("{filter: digits}").unwrap();
+        assert!(!digits.accepts('\n', 0));
+    }
+}
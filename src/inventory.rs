@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Deserializer};
+
+/// Something the player can carry, defined in the init file.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// What the player carries, ids with how many of each.
+#[derive(Debug, Default, Clone)]
+pub struct Inventory(BTreeMap<String, u32>);
+
+impl Inventory {
+    pub fn contains(&self, id: &str) -> bool {
+        self.0.contains_key(id)
+    }
+
+    pub fn count(&self, id: &str) -> u32 {
+        self.0.get(id).copied().unwrap_or(0)
+    }
+
+    /// Adding none doesn't make the item carried.
+    pub fn add(&mut self, id: &str, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        let count = self.0.entry(id.to_string()).or_default();
+        *count = count.saturating_add(amount);
+    }
+
+    /// Returns `false` and keeps everything if there aren't enough.
+    pub fn remove(&mut self, id: &str, amount: u32) -> bool {
+        match self.0.get_mut(id) {
+            Some(count) if *count > amount => *count -= amount,
+            Some(count) if *count == amount => {
+                self.0.remove(id);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The names of what's carried with how many when there's more than one, `None` if nothing is.
+    pub fn listing(&self, items: &HashMap<String, Item>) -> Option<String> {
+        let names: Vec<String> = self
+            .0
+            .iter()
+            .map(|(id, count)| {
+                let name = items.get(id).map_or(id.as_str(), |item| item.name.as_str());
+                match count {
+                    1 => name.to_string(),
+                    _ => format!("{} ({})", name, count),
+                }
+            })
+            .collect();
+        (!names.is_empty()).then(|| names.join(", "))
+    }
+}
+
+/// An amount of an item, written as just its id when it's one.
+#[derive(Debug)]
+pub struct ItemStack {
+    pub item: String,
+    pub amount: u32,
+}
+
+impl<'de> Deserialize<'de> for ItemStack {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Id(String),
+            Stack {
+                item: String,
+                #[serde(default = "one")]
+                amount: u32,
+            },
+        }
+
+        fn one() -> u32 {
+            1
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Id(item) => ItemStack { item, amount: 1 },
+            Raw::Stack { item, amount } => ItemStack { item, amount },
+        })
+    }
+}
+
+/// Lists the inventory, with the lines it starts with.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct InventoryListing {
+    pub carrying: String,
+    pub carrying_nothing: String,
+}
+
+impl Default for InventoryListing {
+    fn default() -> Self {
+        InventoryListing {
+            carrying: "You are carrying:".to_string(),
+            carrying_nothing: "You aren't carrying anything.".to_string(),
+        }
+    }
+}
+
+impl InventoryListing {
+    pub fn describe(&self, inventory: &Inventory, items: &HashMap<String, Item>) -> String {
+        match inventory.listing(items) {
+            Some(listing) => format!("{} {}.", self.carrying, listing),
+            None => self.carrying_nothing.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_none() {
+        let mut inventory = Inventory::default();
+        inventory.add("coin", 0);
+        assert!(!inventory.contains("coin"));
+        assert_eq!(inventory.listing(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn adding_saturates() {
+        let mut inventory = Inventory::default();
+        inventory.add("coin", u32::MAX);
+        inventory.add("coin", 5);
+        assert_eq!(inventory.count("coin"), u32::MAX);
+    }
+
+    #[test]
+    fn removing() {
+        let mut inventory = Inventory::default();
+        inventory.add("coin", 3);
+        assert!(!inventory.remove("coin", 4));
+        assert_eq!(inventory.count("coin"), 3);
+        assert!(inventory.remove("coin", 2));
+        assert_eq!(inventory.count("coin"), 1);
+        assert!(inventory.remove("coin", 1));
+        assert!(!inventory.contains("coin"));
+        assert!(!inventory.remove("coin", 1));
+        assert!(!inventory.remove("key", 1));
+    }
+
+    #[test]
+    fn listing() {
+        let mut inventory = Inventory::default();
+        inventory.add("coin", 2);
+        inventory.add("key", 1);
+        let items = HashMap::from([(
+            "key".to_string(),
+            Item {
+                id: "key".to_string(),
+                name: "brass key".to_string(),
+                description: String::new(),
+            },
+        )]);
+        assert_eq!(
+            inventory.listing(&items).as_deref(),
+            Some("coin (2), brass key")
+        );
+    }
+}
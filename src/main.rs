@@ -2,23 +2,42 @@ use crate::file_format::FileFormat;
 use crate::initializer::Initializer;
 use crate::ui::UI;
 
+mod alignment;
+mod animation;
+mod ask;
+mod canvas;
 mod capture;
 mod character;
 mod character_style;
+mod color_depth;
 mod common;
 mod compiled;
 mod condition;
+mod controls;
 mod executable;
+mod external;
 mod file_format;
+mod frame;
 mod initializer;
+mod input_assist;
+mod inventory;
 mod refer;
 mod section;
+mod settings;
 mod show_input;
 mod switcher;
 mod text_input;
+mod time_span;
+mod transition;
 mod ui;
 mod ui_messenger;
+mod vocabulary;
+mod parser;
 mod path_reference;
+mod picture;
+mod prompt;
+mod read_log;
+mod world;
 
 fn main() {
     handle_yaml();
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// The words commands are made of, verbs and nouns map a name to its synonyms
+/// (which can be more than one word long, like `pick up`), the name itself is always understood.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lexicon {
+    #[serde(default)]
+    pub verbs: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub nouns: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub adjectives: Vec<String>,
+    #[serde(default = "Lexicon::default_articles")]
+    pub articles: Vec<String>,
+    #[serde(default = "Lexicon::default_prepositions")]
+    pub prepositions: Vec<String>,
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Lexicon {
+            verbs: HashMap::new(),
+            nouns: HashMap::new(),
+            adjectives: Vec::new(),
+            articles: Lexicon::default_articles(),
+            prepositions: Lexicon::default_prepositions(),
+        }
+    }
+}
+
+/// A noun and the adjectives written before it, `red key` in `take the red key`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Noun {
+    pub name: String,
+    pub adjectives: Vec<String>,
+}
+
+/// A parsed command, like `put (the red key) in (the box)`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub verb: String,
+    pub object: Option<Noun>,
+    pub preposition: Option<String>,
+    /// The object of the preposition, if the command has a direct object too.
+    pub indirect: Option<Noun>,
+}
+
+impl Lexicon {
+    fn default_articles() -> Vec<String> {
+        ["the", "a", "an", "some"].map(String::from).to_vec()
+    }
+
+    fn default_prepositions() -> Vec<String> {
+        [
+            "in", "into", "on", "onto", "at", "to", "with", "from", "under",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    /// Lowercases every word, as the input is, and adds each verb and noun's name to its synonyms.
+    pub fn normalize(&mut self) {
+        for (name, synonyms) in self.verbs.iter_mut().chain(self.nouns.iter_mut()) {
+            synonyms.push(name.to_owned());
+            for synonym in synonyms.iter_mut() {
+                *synonym = synonym
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .to_lowercase();
+            }
+            synonyms.sort();
+            synonyms.dedup();
+        }
+        for words in [
+            &mut self.adjectives,
+            &mut self.articles,
+            &mut self.prepositions,
+        ] {
+            for word in words.iter_mut() {
+                *word = word.to_lowercase();
+            }
+        }
+    }
+
+    /// Returns `None` if the input doesn't start with a verb or has a word the lexicon doesn't know.
+    pub fn parse(&self, input: &str) -> Option<Command> {
+        let input = input.to_lowercase();
+        let words: Vec<&str> = input
+            .split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '-')
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        let (verb, mut i) = Self::longest_match(&self.verbs, &words)?;
+        let mut command = Command {
+            verb,
+            ..Command::default()
+        };
+        let mut adjectives = Vec::new();
+        while i < words.len() {
+            let word = words[i].to_string();
+            if let Some((name, len)) = Self::longest_match(&self.nouns, &words[i..]) {
+                let noun = Noun {
+                    name,
+                    adjectives: std::mem::take(&mut adjectives),
+                };
+                // Nouns after a preposition are indirect, unless there's no direct object yet (`look at the door`).
+                if command.object.is_none() {
+                    command.object = Some(noun);
+                } else if command.indirect.is_none() {
+                    command.indirect = Some(noun);
+                }
+                i += len;
+                continue;
+            }
+            if self.adjectives.contains(&word) {
+                adjectives.push(word);
+            } else if self.prepositions.contains(&word) {
+                command.preposition.get_or_insert(word);
+            } else if !self.articles.contains(&word) {
+                return None;
+            }
+            i += 1;
+        }
+        Some(command)
+    }
+
+    /// Finds the synonym matching the most words at the start of `words`,
+    /// returns its name and how many words it took.
+    /// Of synonyms as long as each other, the one of the first name in lexical order wins.
+    fn longest_match(
+        synonyms: &HashMap<String, Vec<String>>,
+        words: &[&str],
+    ) -> Option<(String, usize)> {
+        synonyms
+            .iter()
+            .flat_map(|(name, synonyms)| synonyms.iter().map(move |synonym| (name, synonym)))
+            .filter_map(|(name, synonym)| {
+                let len = synonym.split(' ').count();
+                let matches = !synonym.is_empty()
+                    && len <= words.len()
+                    && synonym.split(' ').zip(words).all(|(a, b)| a == *b);
+                matches.then_some((name, len))
+            })
+            .max_by(|(a, a_len), (b, b_len)| a_len.cmp(b_len).then_with(|| b.cmp(a)))
+            .map(|(name, len)| (name.to_owned(), len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexicon() -> Lexicon {
+        let mut lexicon: Lexicon = serde_yaml::from_str(
+            "
+            verbs:
+              take: [Pick Up, grab]
+              pick: []
+              look: []
+              put: []
+            nouns:
+              key: [Brass  Key]
+              box: []
+              door: [brass door]
+            adjectives: [Red, Small]
+            prepositions: [In, At]
+            ",
+        )
+        .unwrap();
+        lexicon.normalize();
+        lexicon
+    }
+
+    fn noun(name: &str, adjectives: &[&str]) -> Noun {
+        Noun {
+            name: name.to_string(),
+            adjectives: adjectives.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn longest_synonym_wins() {
+        let command = lexicon().parse("PICK UP the brass key").unwrap();
+        assert_eq!(command.verb, "take");
+        assert_eq!(command.object, Some(noun("key", &[])));
+        let command = lexicon().parse("pick the box").unwrap();
+        assert_eq!(command.verb, "pick");
+    }
+
+    #[test]
+    fn words_are_compared_in_lowercase() {
+        let command = lexicon().parse("Put the RED small Key IN the Box").unwrap();
+        assert_eq!(
+            command,
+            Command {
+                verb: "put".to_string(),
+                object: Some(noun("key", &["red", "small"])),
+                preposition: Some("in".to_string()),
+                indirect: Some(noun("box", &[])),
+            }
+        );
+    }
+
+    #[test]
+    fn object_after_a_preposition() {
+        let command = lexicon().parse("look at the door").unwrap();
+        assert_eq!(command.object, Some(noun("door", &[])));
+        assert_eq!(command.preposition.as_deref(), Some("at"));
+        assert_eq!(command.indirect, None);
+    }
+
+    #[test]
+    fn unknown_words() {
+        assert_eq!(lexicon().parse("dance"), None);
+        assert_eq!(lexicon().parse("take the spoon"), None);
+        assert_eq!(lexicon().parse(""), None);
+    }
+
+    #[test]
+    fn ties_go_to_the_first_name() {
+        let mut lexicon = lexicon();
+        lexicon
+            .nouns
+            .insert("lock".to_string(), vec!["brass".to_string()]);
+        lexicon
+            .nouns
+            .insert("bell".to_string(), vec!["brass".to_string()]);
+        lexicon.normalize();
+        let command = lexicon.parse("take brass").unwrap();
+        assert_eq!(command.object, Some(noun("bell", &[])));
+    }
+}
@@ -15,8 +15,8 @@ pub enum PathReference {
 }
 
 impl Compiled for PathReference {
-    fn compile(&mut self, _init: &mut InitializerData, base: &PathBuf) -> Checked {
-        let logical = self.logical_path(&base);
+    fn compile(&mut self, _init: &mut InitializerData, base: &Path) -> Checked {
+        let logical = self.logical_path(base);
         if logical.exists() {
             *self = Self::Absolute(logical);
         } else {
@@ -0,0 +1,548 @@
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use image::codecs::gif::GifDecoder;
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, GenericImageView, Rgba, RgbaImage};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::color_depth::ColorDepth;
+use crate::compiled::{Comp, CompileError};
+use crate::frame::{Cell, Frame};
+use crate::time_span::TimeSpan;
+
+/// How an image is turned into text.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageOptions {
+    /// Size in terminal cells, the side left out follows the image's proportions.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Makes the image as big as fits in the frame (or in `width` and `height`, if given).
+    #[serde(default)]
+    pub fit: bool,
+    /// Image pixels per cell when there's no size given.
+    #[serde(default = "ImageOptions::default_scale")]
+    pub scale: u32,
+    #[serde(default)]
+    pub filter: ResizeFilter,
+    /// How many times taller than wide a terminal cell is.
+    #[serde(default = "ImageOptions::default_cell_aspect")]
+    pub cell_aspect: f32,
+    #[serde(default)]
+    pub invert: bool,
+    /// Overrides the `colors` setting.
+    pub colors: Option<ColorDepth>,
+    #[serde(default)]
+    pub mode: RenderMode,
+    /// Glyphs from the darkest to the brightest, for the ascii mode.
+    #[serde(default = "ImageOptions::default_ramp")]
+    #[serde(deserialize_with = "ImageOptions::deserialize_ramp")]
+    pub ramp: Vec<char>,
+    #[serde(default)]
+    pub luminance: Luminance,
+    #[serde(default = "ImageOptions::default_one")]
+    pub gamma: f32,
+    #[serde(default = "ImageOptions::default_one")]
+    pub contrast: f32,
+    /// Added to every channel, from -1 to 1.
+    #[serde(default)]
+    pub brightness: f32,
+    /// The brightness from 0 to 1 above which a dot or half block is drawn.
+    #[serde(default = "ImageOptions::default_threshold")]
+    pub threshold: f32,
+    #[serde(default)]
+    pub dither: Dither,
+    /// The opacity from 0 to 1 pixels need to be above to be drawn at all.
+    #[serde(default)]
+    pub alpha_threshold: f32,
+}
+
+impl ImageOptions {
+    const fn default_scale() -> u32 {
+        2
+    }
+
+    const fn default_cell_aspect() -> f32 {
+        2.0
+    }
+
+    fn default_ramp() -> Vec<char> {
+        " .,-~+=@".chars().collect()
+    }
+
+    const fn default_one() -> f32 {
+        1.0
+    }
+
+    const fn default_threshold() -> f32 {
+        0.5
+    }
+
+    fn deserialize_ramp<'de, D>(deserializer: D) -> Result<Vec<char>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let ramp: Vec<char> = String::deserialize(deserializer)?.chars().collect();
+        if ramp.len() < 2 {
+            return Err(de::Error::custom("a ramp needs at least two glyphs"));
+        }
+        Ok(ramp)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RenderMode {
+    /// A glyph of the ramp for every pixel.
+    #[default]
+    Ascii,
+    /// Two pixels a cell, the top one in front and the bottom one behind.
+    #[serde(alias = "halfBlocks")]
+    #[serde(alias = "half")]
+    HalfBlock,
+    /// Two by four dots a cell.
+    Braille,
+}
+
+impl RenderMode {
+    /// How many pixels wide and tall a cell is.
+    const fn cell_pixels(self) -> (u32, u32) {
+        match self {
+            RenderMode::Ascii => (1, 1),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+}
+
+/// How bright a pixel looks.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Luminance {
+    /// Weighs the channels the way eyes do, green the most and blue the least.
+    #[default]
+    Perceptual,
+    Average,
+}
+
+impl Luminance {
+    /// From 0 to 1.
+    fn of(self, pixel: &Rgba<u8>) -> f32 {
+        match self {
+            Luminance::Perceptual => {
+                (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32)
+                    / 255.0
+            }
+            Luminance::Average => (pixel[0] / 3 + pixel[1] / 3 + pixel[2] / 3) as f32 / 255.0,
+        }
+    }
+}
+
+/// How brightness is spread over the few levels a mode can draw.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Dither {
+    /// Every pixel gets the level its brightness falls in.
+    #[default]
+    None,
+    /// Compares pixels with a repeating pattern of thresholds.
+    #[serde(alias = "bayer")]
+    Ordered,
+    /// Passes on what every pixel was rounded by to the pixels around it.
+    #[serde(alias = "floyd")]
+    FloydSteinberg,
+}
+
+impl Dither {
+    const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+    /// Picks a level below `levels` for every visible pixel, with two levels the second
+    /// one is picked above `threshold` (unless the dithering has its own thresholds).
+    fn levels(
+        self,
+        values: &[Option<f32>],
+        width: usize,
+        levels: usize,
+        threshold: f32,
+    ) -> Vec<Option<usize>> {
+        let top = (levels - 1) as f32;
+        let nearest = |value: f32| match levels {
+            2 => (value > threshold) as usize,
+            _ => (value * top).round().clamp(0.0, top) as usize,
+        };
+        match self {
+            Dither::None => (values.iter())
+                .map(|value| {
+                    value.map(|value| match levels {
+                        2 => (value > threshold) as usize,
+                        _ => ((value * levels as f32) as usize).min(levels - 1),
+                    })
+                })
+                .collect(),
+            Dither::Ordered => (values.iter().enumerate())
+                .map(|(i, value)| {
+                    let (x, y) = (i % width, i / width);
+                    let threshold = (Self::BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0;
+                    value.map(|value| {
+                        let scaled = (value * top).clamp(0.0, top);
+                        let level = scaled.floor() + (scaled.fract() > threshold) as u8 as f32;
+                        level as usize
+                    })
+                })
+                .collect(),
+            Dither::FloydSteinberg => {
+                const SPREAD: [(isize, usize, f32); 4] =
+                    [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+
+                let mut values = values.to_vec();
+                let height = values.len() / width;
+                let mut result = vec![None; values.len()];
+                for y in 0..height {
+                    for x in 0..width {
+                        let value = match values[y * width + x] {
+                            Some(value) => value,
+                            None => continue,
+                        };
+                        let level = nearest(value);
+                        result[y * width + x] = Some(level);
+                        let error = value - level as f32 / top;
+                        for (dx, dy, weight) in SPREAD {
+                            let (x, y) = (x as isize + dx, y + dy);
+                            if x < 0 || x as usize >= width || y >= height {
+                                continue;
+                            }
+                            if let Some(ref mut value) = values[y * width + x as usize] {
+                                *value += error * weight / 16.0;
+                            }
+                        }
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+/// The level picked for every pixel of an image, `None` for the transparent ones.
+struct Levels {
+    width: u32,
+    levels: Vec<Option<usize>>,
+}
+
+impl Levels {
+    fn get(&self, x: u32, y: u32) -> Option<usize> {
+        self.levels[(y * self.width + x) as usize]
+    }
+
+    fn lit(&self, x: u32, y: u32) -> bool {
+        self.get(x, y) == Some(1)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ResizeFilter {
+    Nearest,
+    #[default]
+    #[serde(alias = "linear")]
+    Triangle,
+    #[serde(alias = "cubic")]
+    CatmullRom,
+    Gaussian,
+    #[serde(alias = "lanczos")]
+    Lanczos3,
+}
+
+impl From<ResizeFilter> for FilterType {
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::CatmullRom => FilterType::CatmullRom,
+            ResizeFilter::Gaussian => FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// A decoded image, drawn every time it's shown since its size can depend on the frame's.
+pub struct Picture {
+    image: DynamicImage,
+    options: ImageOptions,
+}
+
+impl fmt::Debug for Picture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Picture")
+            .field("dimensions", &self.image.dimensions())
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl Picture {
+    pub fn load(path: &Path, options: ImageOptions) -> Comp<Self> {
+        if !path.exists() {
+            return Err(CompileError::InvalidPath(path.to_path_buf()));
+        }
+        Ok(Picture {
+            image: image::open(path)?,
+            options,
+        })
+    }
+
+    /// Every frame of an animated GIF, with how long it's shown if the file says.
+    pub fn load_gif(path: &Path, options: ImageOptions) -> Comp<Vec<(Self, Option<TimeSpan>)>> {
+        if !path.exists() {
+            return Err(CompileError::InvalidPath(path.to_path_buf()));
+        }
+        let decoder = GifDecoder::new(BufReader::new(File::open(path)?))?;
+        decoder
+            .into_frames()
+            .map(|frame| {
+                let frame = frame?;
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let millis = (numer / denom.max(1)) as u64;
+                let picture = Picture {
+                    image: DynamicImage::ImageRgba8(frame.into_buffer()),
+                    options: options.clone(),
+                };
+                Ok((picture, (millis > 0).then(|| TimeSpan::from_millis(millis))))
+            })
+            .collect()
+    }
+
+    /// The size in cells the image is drawn at, `frame` being the cells there's room for.
+    fn size(&self, frame: (usize, usize)) -> (u32, u32) {
+        let options = &self.options;
+        let (width, height) = self.image.dimensions();
+        // The size in cells if every pixel took up a column.
+        let natural = (
+            width as f64,
+            height as f64 / options.cell_aspect.max(0.1) as f64,
+        );
+        let (columns, rows) = match (options.width, options.height) {
+            _ if options.fit => {
+                let bounds = (
+                    options.width.map_or(frame.0 as f64, f64::from),
+                    options.height.map_or(frame.1 as f64, f64::from),
+                );
+                let scale = (bounds.0 / natural.0).min(bounds.1 / natural.1);
+                (natural.0 * scale, natural.1 * scale)
+            }
+            (Some(columns), Some(rows)) => (columns as f64, rows as f64),
+            (Some(columns), None) => (columns as f64, columns as f64 * natural.1 / natural.0),
+            (None, Some(rows)) => (rows as f64 * natural.0 / natural.1, rows as f64),
+            (None, None) => {
+                let scale = options.scale.max(1) as f64;
+                (natural.0 / scale, natural.1 / scale)
+            }
+        };
+        (
+            (columns.round() as u32).max(1),
+            (rows.round() as u32).max(1),
+        )
+    }
+
+    /// `colors` is used unless the options have their own.
+    pub fn render(&self, frame: (usize, usize), colors: ColorDepth) -> Frame {
+        let options = &self.options;
+        let colors = options.colors.unwrap_or(colors);
+        let (columns, rows) = self.size(frame);
+        let (width, height) = options.mode.cell_pixels();
+        let mut image = self
+            .image
+            .resize_exact(columns * width, rows * height, options.filter.into())
+            .to_rgba8();
+        self.adjust(&mut image);
+
+        let values: Vec<Option<f32>> = (image.pixels())
+            .map(|pixel| {
+                let visible = pixel[3] as f32 / 255.0 > options.alpha_threshold;
+                visible.then(|| options.luminance.of(pixel))
+            })
+            .collect();
+        let count = match options.mode {
+            RenderMode::Ascii => options.ramp.len(),
+            RenderMode::HalfBlock | RenderMode::Braille => 2,
+        };
+        let levels = Levels {
+            width: image.width(),
+            levels: (options.dither).levels(
+                &values,
+                image.width() as usize,
+                count,
+                options.threshold,
+            ),
+        };
+        let rows = match options.mode {
+            RenderMode::Ascii => self.ascii(&image, &levels, colors),
+            RenderMode::HalfBlock => Self::half_blocks(&image, &levels, colors),
+            RenderMode::Braille => Self::braille(&image, &levels, colors),
+        };
+        Frame { rows }
+    }
+
+    /// Inverts the colors and applies the gamma, contrast and brightness.
+    fn adjust(&self, image: &mut RgbaImage) {
+        let options = &self.options;
+        let gamma = options.gamma.max(0.01);
+        for pixel in image.pixels_mut() {
+            for channel in pixel.0.iter_mut().take(3) {
+                let mut value = *channel as f32 / 255.0;
+                if options.invert {
+                    value = 1.0 - value;
+                }
+                value = value.powf(1.0 / gamma);
+                value = (value - 0.5) * options.contrast + 0.5 + options.brightness;
+                *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+    }
+
+    fn ascii(&self, image: &RgbaImage, levels: &Levels, colors: ColorDepth) -> Vec<Vec<Cell>> {
+        let row = |y: u32| {
+            let cell = |x: u32| match levels.get(x, y) {
+                Some(level) => Cell {
+                    fg: colors.quantize(rgb(image.get_pixel(x, y))),
+                    ..Cell::plain(self.options.ramp[level])
+                },
+                None => Cell::BLANK,
+            };
+            (0..image.width()).map(cell).collect()
+        };
+        (0..image.height()).map(row).collect()
+    }
+
+    fn half_blocks(image: &RgbaImage, levels: &Levels, colors: ColorDepth) -> Vec<Vec<Cell>> {
+        let row = |y: u32| {
+            let cell = |x: u32| {
+                if colors == ColorDepth::None {
+                    let ch = match (levels.lit(x, y), levels.lit(x, y + 1)) {
+                        (false, false) => ' ',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (true, true) => '█',
+                    };
+                    return Cell::plain(ch);
+                }
+                let top = colors.quantize(rgb(image.get_pixel(x, y)));
+                let bottom = colors.quantize(rgb(image.get_pixel(x, y + 1)));
+                match (levels.get(x, y).is_some(), levels.get(x, y + 1).is_some()) {
+                    (false, false) => Cell::BLANK,
+                    (true, false) => Cell {
+                        fg: top,
+                        ..Cell::plain('▀')
+                    },
+                    (false, true) => Cell {
+                        fg: bottom,
+                        ..Cell::plain('▄')
+                    },
+                    (true, true) => Cell {
+                        ch: '▀',
+                        fg: top,
+                        bg: bottom,
+                    },
+                }
+            };
+            (0..image.width()).map(cell).collect()
+        };
+        (0..image.height()).step_by(2).map(row).collect()
+    }
+
+    fn braille(image: &RgbaImage, levels: &Levels, colors: ColorDepth) -> Vec<Vec<Cell>> {
+        // The bit of every dot, by row and column.
+        const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+        let cell = |column: u32, row: u32| {
+            let mut bits = 0;
+            let mut lit = Vec::new();
+            for (dy, dots) in DOTS.iter().enumerate() {
+                for (dx, dot) in dots.iter().enumerate() {
+                    let (x, y) = (column * 2 + dx as u32, row * 4 + dy as u32);
+                    if levels.lit(x, y) {
+                        bits |= dot;
+                        lit.push(rgb(image.get_pixel(x, y)));
+                    }
+                }
+            }
+            if bits == 0 {
+                return Cell::BLANK;
+            }
+            let average = [0, 1, 2].map(|channel| {
+                let sum: u32 = lit.iter().map(|rgb| rgb[channel] as u32).sum();
+                (sum / lit.len() as u32) as u8
+            });
+            Cell {
+                fg: colors.quantize(average),
+                ..Cell::plain(char::from_u32(0x2800 + bits).unwrap())
+            }
+        };
+        (0..image.height() / 4)
+            .map(|row| {
+                (0..image.width() / 2)
+                    .map(|column| cell(column, row))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn rgb(pixel: &Rgba<u8>) -> [u8; 3] {
+    [pixel[0], pixel[1], pixel[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat(value: f32) -> Vec<Option<f32>> {
+        vec![Some(value); 16]
+    }
+
+    fn lit(levels: &[Option<usize>]) -> usize {
+        levels.iter().filter(|level| **level == Some(1)).count()
+    }
+
+    #[test]
+    fn without_dithering() {
+        let values = [Some(0.2), Some(0.6), None, Some(1.0)];
+        assert_eq!(
+            Dither::None.levels(&values, 2, 2, 0.5),
+            [Some(0), Some(1), None, Some(1)]
+        );
+        assert_eq!(
+            Dither::None.levels(&values, 2, 4, 0.5),
+            [Some(0), Some(2), None, Some(3)]
+        );
+    }
+
+    #[test]
+    fn ordered_spreads_gray_over_the_pattern() {
+        assert_eq!(lit(&Dither::Ordered.levels(&flat(0.5), 4, 2, 0.5)), 8);
+        assert_eq!(lit(&Dither::Ordered.levels(&flat(0.25), 4, 2, 0.5)), 4);
+        assert_eq!(lit(&Dither::Ordered.levels(&flat(0.0), 4, 2, 0.5)), 0);
+        assert_eq!(lit(&Dither::Ordered.levels(&flat(1.0), 4, 2, 0.5)), 16);
+    }
+
+    #[test]
+    fn floyd_steinberg_keeps_the_brightness() {
+        let half = lit(&Dither::FloydSteinberg.levels(&flat(0.5), 4, 2, 0.5));
+        assert!((7..=9).contains(&half), "{} of 16 lit", half);
+        assert_eq!(lit(&Dither::FloydSteinberg.levels(&flat(0.0), 4, 2, 0.5)), 0);
+        assert_eq!(lit(&Dither::FloydSteinberg.levels(&flat(1.0), 4, 2, 0.5)), 16);
+    }
+
+    #[test]
+    fn transparent_pixels_stay_transparent() {
+        let values = [None, Some(0.5), None, Some(0.5)];
+        for dither in [Dither::None, Dither::Ordered, Dither::FloydSteinberg] {
+            let levels = dither.levels(&values, 2, 2, 0.5);
+            assert_eq!((levels[0], levels[2]), (None, None));
+            assert!(levels[1].is_some() && levels[3].is_some());
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+use std::time::Instant;
+
+use serde::Deserialize;
+
+use crate::capture::Capture;
+use crate::compiled::{Checked, Compiled};
+use crate::executable::{Executable, ExecutionState};
+use crate::initializer::InitializerData;
+use crate::input_assist::EntryOptions;
+use crate::section::Section;
+use crate::switcher::Case;
+use crate::time_span::TimeSpan;
+
+/// Reads a line from the player and runs the first case capturing it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Prompt {
+    #[serde(alias = "def")]
+    pub default: Option<Box<Section>>,
+    pub cases: Vec<Case<Capture>>,
+    /// Runs the default branch if nothing was entered in time (not affected by the speed setting).
+    pub timeout: Option<TimeSpan>,
+    /// Shows the time left next to the text input.
+    #[serde(default)]
+    pub countdown: bool,
+    /// When no case matches, the default branch only runs once the attempts run out.
+    #[serde(flatten)]
+    pub retry: RetryOptions,
+    #[serde(flatten)]
+    pub entry: EntryOptions,
+    /// Overrides the `hintAfter` setting.
+    pub hint_after: Option<u32>,
+}
+
+/// Whether an input that isn't accepted is asked for again, shared by prompts and `!ask`.
+#[derive(Debug, Deserialize, Default)]
+#[serde(from = "RawRetryOptions")]
+pub struct RetryOptions {
+    pub retry: bool,
+    /// Printed before asking again.
+    pub retry_message: Option<String>,
+    pub max_attempts: Option<u32>,
+}
+
+/// The aliases can't be serde's, those are left out when the struct is flattened.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRetryOptions {
+    #[serde(default)]
+    retry: bool,
+    retry_message: Option<String>,
+    invalid: Option<String>,
+    max_attempts: Option<u32>,
+    attempts: Option<u32>,
+}
+
+impl From<RawRetryOptions> for RetryOptions {
+    fn from(raw: RawRetryOptions) -> Self {
+        RetryOptions {
+            retry: raw.retry,
+            retry_message: raw.retry_message.or(raw.invalid),
+            max_attempts: raw.max_attempts.or(raw.attempts),
+        }
+    }
+}
+
+impl RetryOptions {
+    /// Whether to ask again once `attempts` inputs weren't accepted,
+    /// if so the retry message (or `default_message`) is printed.
+    pub fn again(
+        &self,
+        attempts: u32,
+        default_message: Option<&str>,
+        execution: &mut ExecutionState,
+    ) -> bool {
+        if !self.retry || self.max_attempts.is_some_and(|max| attempts >= max) {
+            return false;
+        }
+        if let Some(message) = self.retry_message.as_deref().or(default_message) {
+            let message = execution.state.expand_string(message);
+            execution.ui.append(message);
+        }
+        true
+    }
+}
+
+impl Prompt {
+    fn show_hints(&self, execution: &mut ExecutionState) {
+        let hints: Vec<String> = self.cases.iter().filter_map(Case::hint).collect();
+        if !hints.is_empty() {
+            let prefix = &execution.init.settings.hint_prefix;
+            execution
+                .ui
+                .append(format!("{} {}.", prefix, hints.join(", ")));
+        }
+    }
+
+    /// Counts an input in a row that matched no case, the hints are shown once there are enough.
+    fn fail(&self, failures: &mut u32, execution: &mut ExecutionState) {
+        *failures += 1;
+        let hint_after = self.hint_after.or(execution.init.settings.hint_after);
+        if hint_after.is_some_and(|after| *failures >= after) {
+            self.show_hints(execution);
+            *failures = 0;
+        }
+    }
+}
+
+impl Executable for Prompt {
+    fn execute(&self, execution: &mut ExecutionState) {
+        // Asking again doesn't give more time, or commands like `inventory` could stall the prompt.
+        let deadline = self
+            .timeout
+            .map(|timeout| Instant::now() + timeout.duration());
+        let (mut attempts, mut failures) = (0, 0);
+        execution.offer_completions(&self.cases);
+        let case = loop {
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            if timeout.is_some_and(|timeout| timeout.is_zero()) {
+                break None;
+            }
+            match execution.ui.get_entry_input(timeout, self.countdown, &self.entry) {
+                Some(input) => {
+                    let hint_command = &execution.init.settings.hint_command;
+                    let asks_hint = hint_command
+                        .as_ref()
+                        .is_some_and(|command| input.trim().eq_ignore_ascii_case(command));
+                    execution
+                        .state
+                        .set_input(input, execution.init.lexicon.get());
+                    if execution.run_global_command() {
+                        continue;
+                    }
+                    if asks_hint {
+                        self.show_hints(execution);
+                        continue;
+                    }
+                    let case = self.cases.iter().find(|case| case.matches(execution.state));
+                    if case.is_some() {
+                        break case;
+                    }
+                    self.fail(&mut failures, execution);
+                    attempts += 1;
+                    if !self.retry.again(attempts, None, execution) {
+                        break None;
+                    }
+                }
+                None => break None,
+            }
+        };
+
+        if let Some(case) = case {
+            case.execute(execution);
+        } else if let Some(ref section) = self.default {
+            section.execute(execution);
+        }
+    }
+}
+
+impl Compiled for Prompt {
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked {
+        for case in self.cases.iter_mut() {
+            case.compile(init, base)?;
+        }
+        if let Some(ref mut section) = self.default {
+            section.compile(init, base)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ask::Ask;
+
+    #[test]
+    fn retry_options_are_shared() {
+        let prompt: Prompt =
+            serde_yaml::from_str("{cases: [], retry: true, retryMessage: Again, maxAttempts: 3}")
+                .unwrap();
+        let ask: Ask =
+            serde_yaml::from_str("{var: x, retry: true, invalid: Again, attempts: 3}").unwrap();
+        for retry in [&prompt.retry, &ask.retry] {
+            assert!(retry.retry);
+            assert_eq!(retry.retry_message.as_deref(), Some("Again"));
+            assert_eq!(retry.max_attempts, Some(3));
+        }
+        let ask: Ask = serde_yaml::from_str("var: x").unwrap();
+        assert!(!ask.retry.retry);
+    }
+}
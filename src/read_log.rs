@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Every line the player has already read, kept in the user's data directory so it survives between sessions.
+#[derive(Debug)]
+pub struct ReadLog {
+    lines: HashSet<String>,
+    /// Newly read lines are appended to it, one JSON string a line.
+    file: Option<File>,
+}
+
+impl ReadLog {
+    const DIRECTORY: &'static str = "rust_test/read_lines";
+
+    pub fn load<P: AsRef<Path>>(root: P) -> Self {
+        match Self::path(root.as_ref()) {
+            Some(path) => Self::open(&path),
+            None => ReadLog {
+                lines: HashSet::new(),
+                file: None,
+            },
+        }
+    }
+
+    /// One log a project, named after its directory and told apart by a hash of its whole path.
+    fn path(root: &Path) -> Option<PathBuf> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let name = root
+            .file_name()
+            .map_or("story".into(), |name| name.to_string_lossy());
+        // FNV-1a, which stays the same between builds unlike the standard hasher.
+        let hash = (root.to_string_lossy().bytes())
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+            });
+        let file_name = format!("{}-{:016x}.jsonl", name, hash);
+        dirs::data_dir().map(|data| data.join(Self::DIRECTORY).join(file_name))
+    }
+
+    fn open(path: &Path) -> Self {
+        // A missing or broken log just means nothing was read yet.
+        let lines = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        let file = path
+            .parent()
+            .and_then(|directory| fs::create_dir_all(directory).ok())
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(path).ok());
+        ReadLog { lines, file }
+    }
+
+    pub fn contains(&self, line: &str) -> bool {
+        self.lines.contains(line)
+    }
+
+    /// Marks a line as read, returns whether it was read before.
+    pub fn mark(&mut self, line: &str) -> bool {
+        if self.contains(line) {
+            return true;
+        }
+        self.lines.insert(line.to_string());
+        if let (Some(file), Ok(raw)) = (&mut self.file, serde_json::to_string(line)) {
+            // Losing the log isn't worth interrupting the story over.
+            let _ = writeln!(file, "{}", raw);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("read_log_{}_{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn marked_lines_are_loaded_again() {
+        let path = temp_log("round_trip");
+        let mut log = ReadLog::open(&path);
+        assert!(!log.mark("Hello there."));
+        assert!(!log.mark("Two\nlines with \"quotes\""));
+        assert!(log.mark("Hello there."));
+        drop(log);
+
+        let log = ReadLog::open(&path);
+        assert!(log.contains("Hello there."));
+        assert!(log.contains("Two\nlines with \"quotes\""));
+        assert!(!log.contains("Something new"));
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn broken_lines_are_skipped() {
+        let path = temp_log("broken");
+        fs::write(&path, "\"kept\"\nnot json\n").unwrap();
+        let log = ReadLog::open(&path);
+        assert!(log.contains("kept"));
+        assert_eq!(log.lines.len(), 1);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -32,7 +32,7 @@ impl Compiled for Refer {
     fn compile(
         &mut self,
         init: &mut crate::initializer::InitializerData,
-        base: &PathBuf,
+        base: &std::path::Path,
     ) -> crate::compiled::Checked {
         match self {
             Self::Relative(ref mut relative_path) => {
@@ -1,18 +1,23 @@
-use std::path::PathBuf;
+use std::path::Path;
 
 use cursive::theme::Effect;
 use serde::Deserialize;
 
-use crate::capture::Capture;
+use crate::ask::Ask;
 use crate::character::Character;
 use crate::compiled::{Checked, Compiled};
 use crate::condition::{Condition, Conditional};
 use crate::executable::{Executable, ExecutionState};
 use crate::initializer::InitializerData;
+use crate::inventory::{InventoryListing, ItemStack};
+use crate::prompt::Prompt;
 use crate::refer::Refer;
-use crate::show_input::ShowInput;
+use crate::show_input::{LayerMove, ShowInput};
 use crate::switcher::Switcher;
 use crate::text_input::{TextInput, TitleInput};
+use crate::time_span::TimeSpan;
+use crate::transition::Transition;
+use crate::world::World;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,16 +30,18 @@ pub enum Section {
     Text(TextInput),
     Title(TitleInput),
     Print(String),
-    Wait(u64),
+    Wait(TimeSpan),
     #[serde(alias = "ref")]
     Refer(Refer),
+    /// Refers to the next scene, the frame is cleared through the `sceneTransition` setting first.
+    Scene(Refer),
     #[serde(skip_deserializing)]
     #[serde(skip_serializing)]
     PendingCompilation,
     #[serde(alias = "character")]
     CharacterDef(Character),
     #[serde(alias = "in")]
-    Input(Switcher<Capture>),
+    Input(Prompt),
     Switch(Switcher<Conditional>),
     Branch {
         #[serde(alias = "if")]
@@ -45,6 +52,22 @@ pub enum Section {
     },
     Let(String),
     Show(ShowInput),
+    /// Stops the animation playing on a layer, or every animation without one.
+    /// The layers keep the frame they were showing, `!clearFrame` removes them.
+    StopAnimation(Option<String>),
+    /// Stops every animation and removes every layer, through the transition if there's one.
+    ClearFrame(Option<Transition>),
+    MoveLayer(LayerMove),
+    RemoveLayer(String),
+    Ask(Ask),
+    #[serde(alias = "world")]
+    Explore(Box<World>),
+    /// Stops exploring the world once the current command is done.
+    Leave,
+    Give(ItemStack),
+    /// Does nothing if the player doesn't have enough.
+    Take(ItemStack),
+    Inventory(Option<InventoryListing>),
 }
 
 impl Executable for Section {
@@ -60,10 +83,13 @@ impl Executable for Section {
                         .characters
                         .get(speaker)
                         .unwrap_or(&init.default_character);
-                    let speaker = String::from(speaker);
-                    ui.typewrite(c.style_with(speaker, vec![Effect::Underline].as_ref()), 0.2);
-                    let text = String::from(": ") + text;
-                    ui.typewrite_s(c.style(text), input.duration.unwrap_or(c.duration) as f32);
+                    let mut line = c.style_with(String::from(speaker), &[Effect::Underline]);
+                    line.append(c.style(String::from(": ") + text));
+                    ui.say(
+                        line,
+                        input.speed.unwrap_or(c.speed) as f32,
+                        c.reading_delay(&init.settings),
+                    );
                 }
             }
             Section::Text(input) => {
@@ -73,22 +99,28 @@ impl Executable for Section {
                         .get(speaker)
                         .unwrap_or(&init.default_character);
                     let text = String::from(text);
-                    let string = c.style(text);
-                    ui.typewrite_s(string.clone(), input.duration.unwrap_or(c.duration) as f32);
+                    ui.say(
+                        c.style(text),
+                        input.speed.unwrap_or(c.speed) as f32,
+                        c.reading_delay(&init.settings),
+                    );
                 }
             }
             Section::Title(title_input) => title_input.execute(execution),
-            Section::Wait(seconds) => crate::common::sleep(*seconds),
+            Section::Wait(span) => ui.wait(*span),
             Section::Refer(refer) => refer.execute(execution),
+            Section::Scene(refer) => {
+                if let Some(transition) = execution.init.settings.scene_transition {
+                    execution.ui.clear_frame(Some(transition));
+                }
+                refer.execute(execution);
+            }
             Section::Sequence(sections) => {
                 for section in sections {
                     section.execute(execution);
                 }
             }
-            Section::Input(switcher) => {
-                state.update_input(ui);
-                switcher.execute(execution);
-            }
+            Section::Input(prompt) => prompt.execute(execution),
             Section::Branch {
                 conditions,
                 then,
@@ -104,6 +136,27 @@ impl Executable for Section {
             Section::Print(val) => ui.append(state.expand_string(val)),
             Section::Let(expr) => state.var_expr(expr),
             Section::Show(input) => input.execute(execution),
+            Section::StopAnimation(Some(layer)) => ui.stop_animation(layer),
+            Section::StopAnimation(None) => ui.stop_animations(),
+            Section::ClearFrame(transition) => ui.clear_frame(*transition),
+            Section::MoveLayer(layer_move) => layer_move.execute(execution),
+            Section::RemoveLayer(layer) => {
+                ui.remove_layer(layer);
+            }
+            Section::Ask(ask) => ask.execute(execution),
+            Section::Explore(world) => world.execute(execution),
+            Section::Leave => state.world.leave(),
+            Section::Give(stack) => state.give(&stack.item, stack.amount),
+            Section::Take(stack) => {
+                state.take(&stack.item, stack.amount);
+            }
+            Section::Inventory(listing) => {
+                let line = listing
+                    .as_ref()
+                    .unwrap_or(&InventoryListing::default())
+                    .describe(&state.inventory, &init.items);
+                ui.append(line);
+            }
 
             Section::CharacterDef(_) | Section::PendingCompilation => (),
         };
@@ -111,26 +164,31 @@ impl Executable for Section {
 }
 
 impl Compiled for Section {
-    fn compile(&mut self, init: &mut InitializerData, base: &PathBuf) -> Checked {
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked {
         match *self {
             Section::CharacterDef(ref character) => {
                 let c = character.clone();
                 init.characters.insert(c.name.clone(), c);
                 Ok(())
             }
-            Section::Refer(ref mut refer) => refer.compile(init, base),
+            Section::Refer(ref mut refer) | Section::Scene(ref mut refer) => {
+                refer.compile(init, base)
+            }
             Section::Sequence(ref mut sections) => {
                 for section in sections.iter_mut() {
                     section.compile(init, base)?;
                 }
                 Ok(())
             }
-            Section::Input(ref mut switcher) => switcher.compile(init, base),
+            Section::Input(ref mut prompt) => prompt.compile(init, base),
             Section::Branch {
+                ref mut conditions,
                 ref mut then,
                 ref mut otherwise,
-                ..
             } => {
+                for condition in conditions.iter_mut() {
+                    condition.compile(init, base)?;
+                }
                 then.compile(init, base)?;
                 if let Some(val) = otherwise {
                     val.compile(init, base)?;
@@ -139,6 +197,11 @@ impl Compiled for Section {
             }
             Section::Switch(ref mut switcher) => switcher.compile(init, base),
             Section::Show(ref mut input) => input.compile(init, base),
+            Section::Explore(ref mut world) => world.compile(init, base),
+            Section::Give(ref stack) | Section::Take(ref stack) => {
+                init.used_items.push(stack.item.to_owned());
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
@@ -0,0 +1,85 @@
+use serde::{de, Deserialize, Deserializer};
+
+use crate::color_depth::ColorDepth;
+use crate::controls::AdvanceMode;
+use crate::time_span::TimeSpan;
+use crate::transition::Transition;
+
+/// Project wide settings, read from the `settings` key of the init file.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    #[serde(default)]
+    pub advance: AdvanceMode,
+    /// Reading time given for each written character before auto-advancing.
+    #[serde(default = "Settings::default_reading_delay")]
+    pub reading_delay: TimeSpan,
+    /// Multiplies the pace of everything timed: typing, waits, titles, frames and reading delays.
+    #[serde(default = "Settings::default_speed")]
+    #[serde(deserialize_with = "Settings::deserialize_speed")]
+    pub speed: f32,
+    /// Typed at a prompt to show what it accepts.
+    pub hint_command: Option<String>,
+    /// Inputs in a row matching no case after which a prompt shows what it accepts.
+    pub hint_after: Option<u32>,
+    #[serde(default = "Settings::default_hint_prefix")]
+    pub hint_prefix: String,
+    /// Up and Down bring back earlier inputs.
+    #[serde(default = "Settings::default_on")]
+    pub history: bool,
+    /// Tab completes the input with what the prompt accepts.
+    #[serde(default = "Settings::default_on")]
+    pub completion: bool,
+    /// The colors images are drawn with, unless they say otherwise.
+    #[serde(default)]
+    pub colors: ColorDepth,
+    /// Clears the frame through this transition whenever a `!scene` goes to the next one.
+    pub scene_transition: Option<Transition>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            advance: AdvanceMode::default(),
+            reading_delay: Settings::default_reading_delay(),
+            speed: Settings::default_speed(),
+            hint_command: None,
+            hint_after: None,
+            hint_prefix: Settings::default_hint_prefix(),
+            history: Settings::default_on(),
+            completion: Settings::default_on(),
+            colors: ColorDepth::default(),
+            scene_transition: None,
+        }
+    }
+}
+
+impl Settings {
+    fn default_reading_delay() -> TimeSpan {
+        TimeSpan::from_millis(50)
+    }
+
+    fn default_speed() -> f32 {
+        1.0
+    }
+
+    fn default_on() -> bool {
+        true
+    }
+
+    fn default_hint_prefix() -> String {
+        "You could try:".to_string()
+    }
+
+    fn deserialize_speed<'de, D>(deserializer: D) -> Result<f32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let speed = f32::deserialize(deserializer)?;
+        if speed.is_finite() && speed > 0.0 {
+            Ok(speed)
+        } else {
+            Err(de::Error::custom("speed must be a positive number"))
+        }
+    }
+}
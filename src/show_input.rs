@@ -1,129 +1,160 @@
+use std::fs;
+
 use serde::Deserialize;
 
 use crate::{
     compiled::{Checked, Compiled},
     executable::Executable,
 };
+use crate::alignment::Anchor;
+use crate::canvas::Placement;
 use crate::executable::ExecutionState;
+use crate::color_depth::ColorDepth;
+use crate::frame::Frame;
 use crate::path_reference::PathReference;
-
-#[derive(Debug, Deserialize, Clone, Copy)]
-#[serde(rename_all = "camelCase")]
-pub enum Alignment {
-    Center,
-    #[serde(alias = "default")]
-    TopLeft,
-}
-
-impl Default for Alignment {
-    fn default() -> Self {
-        Self::TopLeft
-    }
-}
-
-impl Executable for Alignment {
-    fn execute(&self, execution: &mut ExecutionState) {
-        // TODO: Remove the clone call (dereferencing clones it since it implements Copy...)
-        execution.ui.align_frame(*self);
-    }
-}
+use crate::picture::{ImageOptions, Picture};
+use crate::time_span::TimeSpan;
+use crate::transition::Transition;
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum ShowType {
     #[serde(skip_deserializing)]
     #[serde(skip_serializing)]
-    Dry(String),
+    Picture(Picture),
+    Animation {
+        frames: Vec<AnimationFrame>,
+    },
+    /// Drawn as it's written, from the YAML or from a `.txt` file.
+    Art {
+        art: String,
+    },
     Path {
         file: PathReference,
-        #[serde(default = "ShowType::default_scale")]
-        scale: u32,
-        #[serde(default = "ShowType::default_invert")]
-        invert: bool,
+        #[serde(flatten)]
+        options: ImageOptions,
     },
 }
 
 impl ShowType {
-    pub const fn default_scale() -> u32 {
-        2
-    }
-
-    pub const fn default_invert() -> bool {
-        false
-    }
-
-    pub fn get_frame(&self) -> String {
+    /// What to draw with how long each frame is shown, if it says.
+    pub fn get_frames(
+        &self,
+        frame_size: (usize, usize),
+        colors: ColorDepth,
+    ) -> Vec<(Frame, Option<TimeSpan>)> {
         match &self {
-            ShowType::Dry(frame) => frame.to_owned(),
-            ShowType::Path { .. } => "".to_string(),
+            ShowType::Picture(picture) => vec![(picture.render(frame_size, colors), None)],
+            ShowType::Animation { frames } => frames
+                .iter()
+                .flat_map(|frame| {
+                    frame
+                        .frame
+                        .get_frames(frame_size, colors)
+                        .into_iter()
+                        .map(|(drawn, delay)| (drawn, frame.delay.or(delay)))
+                })
+                .collect(),
+            ShowType::Art { art } => vec![(Frame::from_text(art), None)],
+            ShowType::Path { .. } => unreachable!("images are loaded when compiled"),
         }
     }
-
-    pub fn position(&mut self, x: Option<usize>, y: Option<usize>) {
-        match self {
-            Self::Dry(s) => {
-                let mut r = "".to_string();
-                if let Some(y) = y {
-                    r = "\n".repeat(y);
-                }
-                if let Some(x) = x {
-                    for l in s.lines() {
-                        r += &" ".repeat(x);
-                        r += l;
-                        r += "\n";
-                    }
-                }
-                *self = Self::Dry(if r.is_empty() { s.to_string() } else { r });
-            }
-            Self::Path { .. } => (),
-        };
-    }
 }
 
 impl Compiled for ShowType {
     fn compile(
         &mut self,
         init: &mut crate::initializer::InitializerData,
-        base: &std::path::PathBuf,
+        base: &std::path::Path,
     ) -> Checked {
         match self {
-            ShowType::Dry(_) => (),
+            ShowType::Picture(_) | ShowType::Art { .. } => (),
+            ShowType::Animation { frames } => {
+                for frame in frames {
+                    frame.frame.compile(init, base)?;
+                }
+            }
             ShowType::Path {
                 ref mut file,
-                scale,
-                invert,
+                options,
             } => {
                 file.compile(init, base)?;
                 let file = file.logical_path(base);
-                let frame = crate::ui::UI::get_image(file, *scale, *invert)?;
-                *self = ShowType::Dry(frame);
+                let extension = file.extension().and_then(|extension| extension.to_str());
+                *self = if extension == Some("txt") {
+                    ShowType::Art {
+                        art: fs::read_to_string(&file)?,
+                    }
+                } else if extension == Some("gif") {
+                    let frames = Picture::load_gif(&file, options.clone())?
+                        .into_iter()
+                        .map(|(picture, delay)| AnimationFrame {
+                            frame: ShowType::Picture(picture),
+                            delay,
+                        })
+                        .collect();
+                    ShowType::Animation { frames }
+                } else {
+                    ShowType::Picture(Picture::load(&file, options.clone())?)
+                };
             }
         };
         Ok(())
     }
 }
 
+/// One step of an animation.
+#[derive(Debug, Deserialize)]
+pub struct AnimationFrame {
+    #[serde(flatten)]
+    pub frame: ShowType,
+    pub delay: Option<TimeSpan>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ShowInput {
     #[serde(flatten)]
     pub frame: ShowType,
-    pub duration: Option<u64>,
-    pub x: Option<usize>,
-    pub y: Option<usize>,
-    #[serde(alias = "align")]
+    pub duration: Option<TimeSpan>,
+    /// Draws on this layer instead of replacing the whole frame.
+    pub layer: Option<String>,
+    #[serde(flatten)]
+    pub placement: Placement,
+    /// Places the whole frame, a `!show` on a layer only does if it gives an alignment.
+    #[serde(flatten)]
+    pub anchor: Anchor,
+    /// Starts the animation over once it's done.
+    #[serde(rename = "loop")]
     #[serde(default)]
-    pub alignment: Alignment,
+    pub looping: bool,
+    /// Lets the story go on while the animation plays, until the next `!show` or `!stopAnimation`.
+    #[serde(default)]
+    pub background: bool,
+    /// How long frames are shown when neither they nor the GIF say.
+    #[serde(default = "ShowInput::default_delay")]
+    pub delay: TimeSpan,
+    pub transition: Option<Transition>,
+}
+
+impl ShowInput {
+    fn default_delay() -> TimeSpan {
+        TimeSpan::from_millis(100)
+    }
 }
 
 impl Executable for ShowInput {
     fn execute(&self, execution: &mut ExecutionState) {
-        self.alignment.execute(execution);
-        let frame = self.frame.get_frame();
-        execution.ui.set_frame(frame);
-        if let Some(dur) = self.duration {
-            crate::common::sleep(dur);
-            execution.ui.clear_frame();
+        if self.layer.is_none() || self.anchor.alignment.is_some() {
+            execution.ui.align_frame(self.anchor);
         }
+        let colors = execution.init.settings.colors;
+        let frames = self
+            .frame
+            .get_frames(execution.ui.frame_size(), colors)
+            .into_iter()
+            .map(|(frame, delay)| (frame, delay.unwrap_or(self.delay)))
+            .collect();
+        execution.ui.animate(self, frames);
     }
 }
 
@@ -131,10 +162,27 @@ impl Compiled for ShowInput {
     fn compile(
         &mut self,
         init: &mut crate::initializer::InitializerData,
-        base: &std::path::PathBuf,
+        base: &std::path::Path,
     ) -> Checked {
-        self.frame.compile(init, base)?;
-        self.frame.position(self.x, self.y);
-        Ok(())
+        self.frame.compile(init, base)
+    }
+}
+
+/// Moves a layer shown before, what's left out stays as it was.
+#[derive(Debug, Deserialize)]
+pub struct LayerMove {
+    pub layer: String,
+    pub x: Option<usize>,
+    pub y: Option<usize>,
+    pub z: Option<i32>,
+}
+
+impl Executable for LayerMove {
+    fn execute(&self, execution: &mut ExecutionState) {
+        if !execution.ui.move_layer(&self.layer, self.x, self.y, self.z) {
+            execution
+                .ui
+                .append_err(&format!("There's no layer \"{}\" to move", self.layer));
+        }
     }
 }
@@ -1,11 +1,11 @@
-use std::path::PathBuf;
+use std::path::Path;
 
 use serde::Deserialize;
 
 use crate::compiled::{Checked, Compiled};
 use crate::condition::Condition;
 use crate::executable::{Executable, ExecutionState};
-use crate::initializer::{InitializerData};
+use crate::initializer::{InitializerData, RuntimeState};
 use crate::section::Section;
 
 #[derive(Debug, Deserialize)]
@@ -22,13 +22,9 @@ where
     T: Condition,
 {
     fn execute(&self, execution: &mut ExecutionState) {
-        for case in self.cases.iter() {
-            if case.captures.iter().all(|cap| cap.value(execution.state)) {
-                case.section.execute(execution);
-                return;
-            }
-        }
-        if let Some(ref section) = self.default {
+        if let Some(case) = self.cases.iter().find(|case| case.matches(execution.state)) {
+            case.execute(execution);
+        } else if let Some(ref section) = self.default {
             section.execute(execution);
         }
     }
@@ -36,11 +32,11 @@ where
 
 impl<T> Compiled for Switcher<T>
 where
-    T: Condition,
+    T: Condition + Compiled,
 {
-    fn compile(&mut self, init: &mut InitializerData, base: &PathBuf) -> Checked {
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked {
         for case in self.cases.iter_mut() {
-            case.section.compile(init, base)?;
+            case.compile(init, base)?;
         }
         if let Some(ref mut section) = self.default {
             section.compile(init, base)?;
@@ -55,4 +51,49 @@ pub struct Case<T: Condition> {
     pub section: Section,
     #[serde(alias = "cap")]
     pub captures: Vec<T>,
+    /// Shown instead of the hint made from the captures.
+    pub hint: Option<String>,
+}
+
+impl<T> Case<T>
+where
+    T: Condition,
+{
+    pub fn matches(&self, state: &RuntimeState) -> bool {
+        self.captures.iter().all(|cap| cap.value(state))
+    }
+
+    pub fn hint(&self) -> Option<String> {
+        if self.hint.is_some() {
+            return self.hint.clone();
+        }
+        let hints: Option<Vec<String>> = self.captures.iter().map(Condition::hint).collect();
+        hints.filter(|hints| !hints.is_empty()).map(|hints| hints.join(" "))
+    }
+}
+
+impl<T> Executable for Case<T>
+where
+    T: Condition,
+{
+    fn execute(&self, execution: &mut ExecutionState) {
+        for capture in self.captures.iter() {
+            if let Err(e) = capture.bind(execution.state) {
+                execution.ui.append_err(&e.to_string());
+            }
+        }
+        self.section.execute(execution);
+    }
+}
+
+impl<T> Compiled for Case<T>
+where
+    T: Condition + Compiled,
+{
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked {
+        for capture in self.captures.iter_mut() {
+            capture.compile(init, base)?;
+        }
+        self.section.compile(init, base)
+    }
 }
@@ -5,7 +5,9 @@ use lazy_static::lazy_static;
 use serde::Deserialize;
 
 
+use crate::alignment::Anchor;
 use crate::executable::{Executable, ExecutionState};
+use crate::time_span::TimeSpan;
 
 
 #[derive(Debug, Deserialize)]
@@ -13,7 +15,9 @@ use crate::executable::{Executable, ExecutionState};
 pub struct TextInput {
     #[serde(flatten)]
     pub dialogs: HashMap<String, String>,
-    pub duration: Option<u64>,
+    /// Overrides the speaker's typing speed, in characters per second.
+    #[serde(alias = "duration")]
+    pub speed: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,7 +25,9 @@ pub struct TextInput {
 pub struct TitleInput {
     pub text: String,
     #[serde(default = "TitleInput::default_duration")]
-    pub wait: u64,
+    pub wait: TimeSpan,
+    #[serde(flatten)]
+    pub anchor: Anchor,
 }
 
 lazy_static! {
@@ -29,11 +35,11 @@ lazy_static! {
 }
 
 impl TitleInput {
-    pub fn default_duration() -> u64 {
-        1
+    pub fn default_duration() -> TimeSpan {
+        TimeSpan::from_secs(1)
     }
 
-    pub fn figure(&self) -> FIGure {
+    pub fn figure(&self) -> FIGure<'_> {
         STD_FONT.convert(&self.text).unwrap()
     }
 }
@@ -0,0 +1,123 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{de, Deserialize, Deserializer};
+
+/// A length of time, written either as seconds (`2`, `1.5`) or with a unit suffix (`300ms`, `2s`, `1m`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeSpan(Duration);
+
+impl TimeSpan {
+    pub const fn from_secs(secs: u64) -> Self {
+        TimeSpan(Duration::from_secs(secs))
+    }
+
+    pub const fn from_millis(millis: u64) -> Self {
+        TimeSpan(Duration::from_millis(millis))
+    }
+
+    #[inline]
+    pub fn duration(self) -> Duration {
+        self.0
+    }
+
+    /// The duration when everything runs `speed` times faster.
+    #[inline]
+    pub fn scaled(self, speed: f32) -> Duration {
+        self.0.div_f64(speed as f64)
+    }
+
+    fn from_secs_f64(secs: f64) -> Result<Self, String> {
+        Duration::try_from_secs_f64(secs)
+            .map(TimeSpan)
+            .map_err(|_| format!("{} is not a valid amount of seconds", secs))
+    }
+}
+
+impl FromStr for TimeSpan {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| c.is_alphabetic())
+            .unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        let amount: f64 = amount
+            .trim()
+            .parse()
+            .map_err(|_| format!("\"{}\" is not a valid duration", s))?;
+        let secs = match unit {
+            "ms" => amount / 1000.0,
+            "" | "s" => amount,
+            "m" => amount * 60.0,
+            _ => return Err(format!("Unknown time unit \"{}\" (use ms, s or m)", unit)),
+        };
+        Self::from_secs_f64(secs)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeSpan {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Seconds(f64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(secs) => TimeSpan::from_secs_f64(secs),
+            Raw::Text(s) => s.parse(),
+        }
+        .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Duration, String> {
+        s.parse::<TimeSpan>().map(TimeSpan::duration)
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(parse("300ms"), Ok(Duration::from_millis(300)));
+        assert_eq!(parse("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse("1m"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse(" 1.5 s "), Ok(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn plain_numbers_are_seconds() {
+        assert_eq!(parse("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse("2"), Ok(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn bad_input() {
+        assert!(parse("").is_err());
+        assert!(parse("ms").is_err());
+        assert!(parse("2h").is_err());
+        assert!(parse("-1s").is_err());
+        assert!(parse("fast").is_err());
+    }
+
+    #[test]
+    fn yaml_numbers_and_strings() {
+        let spans: Vec<TimeSpan> = serde_yaml::from_str("[1.5, 2, 300ms]").unwrap();
+        assert_eq!(
+            spans,
+            [
+                TimeSpan::from_millis(1500),
+                TimeSpan::from_secs(2),
+                TimeSpan::from_millis(300)
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use cursive::theme::{BaseColor, Color};
+use serde::{Deserialize, Deserializer};
+
+use crate::frame::{Cell, Frame};
+use crate::time_span::TimeSpan;
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum TransitionEffect {
+    /// Dims what was shown down to black, then brightens what's shown next.
+    Fade,
+    #[serde(alias = "wipe")]
+    WipeRight,
+    WipeLeft,
+    WipeDown,
+    WipeUp,
+    /// Swaps the cells one at a time, in a random order.
+    Dissolve,
+    /// Types what's shown next line by line, like dialog.
+    Reveal,
+}
+
+/// How the frame goes from what it showed to what it shows next.
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    pub effect: TransitionEffect,
+    pub duration: TimeSpan,
+}
+
+impl Transition {
+    fn default_duration() -> TimeSpan {
+        TimeSpan::from_millis(500)
+    }
+}
+
+impl<'de> Deserialize<'de> for Transition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Effect(TransitionEffect),
+            Full {
+                effect: TransitionEffect,
+                duration: Option<TimeSpan>,
+            },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Effect(effect) => Transition {
+                effect,
+                duration: Transition::default_duration(),
+            },
+            Raw::Full { effect, duration } => Transition {
+                effect,
+                duration: duration.unwrap_or_else(Transition::default_duration),
+            },
+        })
+    }
+}
+
+impl TransitionEffect {
+    /// What the frame looks like `progress` (from 0 to 1) of the way from `from` to `to`.
+    pub fn blend(self, from: &Frame, to: &Frame, progress: f32) -> Frame {
+        let width = from.width().max(to.width());
+        let height = from.rows.len().max(to.rows.len());
+        let rows = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let (from, to) = (from.cell(x, y), to.cell(x, y));
+                        let passed = |position: usize, length: usize| {
+                            (position as f32) < progress * length as f32
+                        };
+                        match self {
+                            TransitionEffect::Fade if progress < 0.5 => {
+                                dim(from, 1.0 - progress * 2.0)
+                            }
+                            TransitionEffect::Fade => dim(to, progress * 2.0 - 1.0),
+                            TransitionEffect::WipeRight if passed(x, width) => to,
+                            TransitionEffect::WipeLeft if passed(width - 1 - x, width) => to,
+                            TransitionEffect::WipeDown if passed(y, height) => to,
+                            TransitionEffect::WipeUp if passed(height - 1 - y, height) => to,
+                            TransitionEffect::Dissolve if scatter(x, y) < progress => to,
+                            TransitionEffect::Reveal if passed(y * width + x, width * height) => to,
+                            _ => from,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Frame { rows }
+    }
+}
+
+/// Darkens the cell, `brightness` going from 0 (blank) to 1 (as it is).
+fn dim(cell: Cell, brightness: f32) -> Cell {
+    if brightness <= 0.0 {
+        return Cell::BLANK;
+    }
+    let scale = |color: Color| match color {
+        Color::Rgb(r, g, b) => {
+            let channel = |c: u8| (c as f32 * brightness) as u8;
+            Color::Rgb(channel(r), channel(g), channel(b))
+        }
+        // Palette colors can't be darkened gradually.
+        _ if brightness < 0.5 => Color::Light(BaseColor::Black),
+        color => color,
+    };
+    Cell {
+        ch: cell.ch,
+        fg: Some(cell.fg.map_or_else(|| scale(Color::TerminalDefault), scale)),
+        bg: cell.bg.map(scale),
+    }
+}
+
+/// A number between 0 and 1 that looks random but is always the same for a cell.
+fn scatter(x: usize, y: usize) -> f32 {
+    let mut hash = (x as u32).wrapping_mul(0x9E37_79B9) ^ (y as u32).wrapping_mul(0x85EB_CA6B);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+    (hash % 10_000) as f32 / 10_000.0
+}
@@ -1,4 +1,3 @@
-use std::path::Path;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 
@@ -8,19 +7,24 @@ use cursive::theme::{BorderStyle, Palette, Theme};
 use cursive::traits::{Nameable, Resizable};
 use cursive::view::{Margins, ScrollStrategy, SizeConstraint};
 use cursive::views::{
-    DummyView, LinearLayout, OnEventView, PaddedView, Panel, ResizedView, ScrollView,
+    DummyView, LinearLayout, NamedView, OnEventView, PaddedView, Panel, ResizedView, ScrollView,
     StackView, TextArea, TextContent, TextView,
 };
-use cursive_aligned_view::Alignable;
 
 use crate::{FileFormat, Initializer};
-use crate::compiled::{Comp, CompileError};
+use crate::alignment::{Alignment, Anchor};
+use crate::controls::Controls;
+use crate::input_assist::InputAssist;
+use crate::read_log::ReadLog;
 use crate::ui_messenger::UIMessenger;
 
 pub struct UI {
     siv: CursiveRunnable,
     text_content: TextContent,
     draw_content: TextContent,
+    status_content: TextContent,
+    controls: Controls,
+    assist: InputAssist,
 }
 
 impl Default for UI {
@@ -35,19 +39,28 @@ impl UI {
 
         let text_content = TextContent::new("");
         let draw_content = TextContent::new("");
+        let status_content = TextContent::new("");
 
         let (tx, rx) = mpsc::channel();
+        let assist = InputAssist::default();
 
         siv.add_fullscreen_layer(
             StackView::new()
                 .fullscreen_layer(
-                    TextView::new_with_content(draw_content.clone())
-                        .no_wrap()
-                        .align_center()
+                    Anchor::default()
+                        .wrap(
+                            TextView::new_with_content(draw_content.clone()).no_wrap(),
+                            Alignment::TopLeft,
+                        )
                         .with_name("frame-view")
                         .full_screen(),
                 )
-                .transparent_layer(Self::textview(&text_content, tx)),
+                .transparent_layer(Self::textview(
+                    &text_content,
+                    &status_content,
+                    tx,
+                    assist.clone(),
+                )),
         );
 
         (
@@ -55,6 +68,9 @@ impl UI {
                 siv,
                 text_content,
                 draw_content,
+                status_content,
+                controls: Controls::default(),
+                assist,
             },
             rx,
         )
@@ -66,34 +82,31 @@ impl UI {
     }
 
     pub fn run(&mut self, root: &str, rx: Receiver<String>) {
-        let cb_sink = self.cb_sink().clone();
-
-        let content = self.text_content.clone();
-        let draw_content = self.draw_content.clone();
+        let m = UIMessenger::new(
+            self.text_content.clone(),
+            self.draw_content.clone(),
+            self.status_content.clone(),
+            self.cb_sink().clone(),
+            rx,
+            self.controls.clone(),
+            self.assist.clone(),
+            ReadLog::load(root),
+        );
         let root = root.to_owned();
 
         self.siv
             .add_global_callback(Event::Key(Key::Esc), |s| s.quit());
+        self.controls.register(&mut self.siv);
 
         // Generate data in a separate thread.
         thread::spawn(move || {
-            Self::execute_sections(cb_sink, &root, content, draw_content, rx);
+            Self::execute_sections(m, &root);
         });
 
         self.siv.run();
     }
 
-    // We will only simulate log generation here.
-    // In real life, this may come from a running task, a separate process, ...
-    fn execute_sections(
-        cb_sink: CbSink,
-        root: &str,
-        content: TextContent,
-        frame_content: TextContent,
-        input_receiver: Receiver<String>,
-    ) {
-        let mut m = UIMessenger::new(content, frame_content, cb_sink, input_receiver);
-
+    fn execute_sections(mut m: UIMessenger, root: &str) {
         let initializer = Initializer::new(root.to_owned(), FileFormat::Yaml);
         if let Ok(mut initializer) = initializer {
             initializer.execute(m);
@@ -124,7 +137,12 @@ impl UI {
         siv
     }
 
-    fn textview(text_content: &TextContent, tx: Sender<String>) -> LinearLayout {
+    fn textview(
+        text_content: &TextContent,
+        status_content: &TextContent,
+        tx: Sender<String>,
+        assist: InputAssist,
+    ) -> LinearLayout {
         LinearLayout::vertical()
             .child(DummyView.full_height())
             .child(PaddedView::new(
@@ -139,66 +157,164 @@ impl UI {
                                 TextView::new_with_content(text_content.clone())
                                     .with_name("text-output"),
                             )
-                            .child(
-                                OnEventView::new(
-                                    TextArea::new().disabled().with_name("text-input"),
-                                )
-                                .on_pre_event_inner(
-                                    EventTrigger::from(Key::Enter),
-                                    move |v, _e| {
-                                        let mut v = v.get_mut();
-                                        let text = v.get_content();
-                                        tx.send(text.to_string());
-                                        v.set_content("");
-                                        Some(EventResult::consumed())
-                                    },
-                                ),
-                            ),
+                            .child(Self::input_row(status_content, tx, assist)),
                     )
                     .scroll_strategy(ScrollStrategy::StickToBottom),
                 )),
             ))
     }
 
-    const fn get_str_ascii(intent: u8) -> &'static str {
-        let index = intent / 32;
-        const ASCII: [&str; 8] = [" ", ".", ",", "-", "~", "+", "=", "@"];
-        ASCII[index as usize]
-    }
-
-    pub fn get_image<P>(dir: P, scale: u32, invert: bool) -> Comp<String>
-        where
-            P: AsRef<Path>,
-    {
-        use image::GenericImageView;
-
-        let mut output = String::from("");
-        let dir: &Path = dir.as_ref();
-        if !dir.exists() {
-            return Err(CompileError::InvalidPath(dir.to_path_buf()));
-        }
-        let img = image::open(dir)?;
-        let (width, height) = img.dimensions();
-        for y in 0..height {
-            for x in 0..width {
-                if y % (scale * 2) == 0 && x % scale == 0 {
-                    let mut pix = img.get_pixel(x, y);
-                    if invert {
-                        for i in 0..3 {
-                            pix[i] = 255 - pix[i];
-                        }
-                    }
-                    let mut intent = pix[0] / 3 + pix[1] / 3 + pix[2] / 3;
-                    if pix[3] == 0 {
-                        intent = 0;
-                    }
-                    output += Self::get_str_ascii(intent);
-                }
+    fn input_row(
+        status_content: &TextContent,
+        tx: Sender<String>,
+        assist: InputAssist,
+    ) -> LinearLayout {
+        // Replaces the input with what the assist comes up with, if anything.
+        fn assisted(
+            v: &mut NamedView<TextArea>,
+            assist: impl Fn(&str) -> Option<String>,
+        ) -> Option<EventResult> {
+            let mut v = v.get_mut();
+            if !v.is_enabled() {
+                return None;
             }
-            if y % (scale * 2) == 0 {
-                output += "\n";
+            if let Some(text) = assist(v.get_content()) {
+                v.set_content(&text);
+                v.set_cursor(text.len());
             }
+            Some(EventResult::consumed())
         }
-        Ok(output)
+
+        let submit = {
+            let assist = assist.clone();
+            move |v: &mut TextArea| {
+                let text = match assist.entry().mask {
+                    Some(_) => assist.take_hidden(),
+                    None => v.get_content().to_string(),
+                };
+                assist.submit(&text);
+                let _ = tx.send(text);
+                v.set_content("");
+            }
+        };
+        let submit_lines = submit.clone();
+        let (entering, ending, typing, erasing) =
+            (assist.clone(), assist.clone(), assist.clone(), assist.clone());
+        let (previous, next, moving) = (assist.clone(), assist.clone(), assist.clone());
+        LinearLayout::horizontal()
+            .child(
+                OnEventView::new(TextArea::new().disabled().with_name("text-input"))
+                    .on_pre_event_inner(EventTrigger::from(Key::Enter), move |v, _e| {
+                        let mut v = v.get_mut();
+                        // Let the global controls have it while there's nothing to submit.
+                        if !v.is_enabled() {
+                            return None;
+                        }
+                        let entry = entering.entry();
+                        if !entry.multiline {
+                            submit(&mut v);
+                            return Some(EventResult::consumed());
+                        }
+                        // Multi-line input takes it as a new line, if one can be typed.
+                        let length = match entry.mask {
+                            Some(_) => entering.hidden_length(),
+                            None => v.get_content().chars().count(),
+                        };
+                        if entry.accepts('\n', length) {
+                            entry.mask?;
+                            let shown = entering.type_hidden('\n');
+                            v.set_content(&shown);
+                            v.set_cursor(shown.len());
+                        }
+                        Some(EventResult::consumed())
+                    })
+                    .on_pre_event_inner(Event::CtrlChar('d'), move |v, _e| {
+                        let mut v = v.get_mut();
+                        if !v.is_enabled() || !ending.entry().multiline {
+                            return None;
+                        }
+                        submit_lines(&mut v);
+                        Some(EventResult::consumed())
+                    })
+                    .on_pre_event_inner(
+                        EventTrigger::from_fn(|e| matches!(e, Event::Char(_))),
+                        move |v, e| {
+                            let mut v = v.get_mut();
+                            let c = match e {
+                                Event::Char(c) if v.is_enabled() => *c,
+                                _ => return None,
+                            };
+                            let entry = typing.entry();
+                            let length = match entry.mask {
+                                Some(_) => typing.hidden_length(),
+                                None => v.get_content().chars().count(),
+                            };
+                            if entry.accepts(c, length) {
+                                // The text area types unmasked characters itself.
+                                entry.mask?;
+                                let shown = typing.type_hidden(c);
+                                v.set_content(&shown);
+                                v.set_cursor(shown.len());
+                            }
+                            Some(EventResult::consumed())
+                        },
+                    )
+                    .on_pre_event_inner(Key::Backspace, move |v, _e| {
+                        let mut v = v.get_mut();
+                        if !v.is_enabled() {
+                            return None;
+                        }
+                        erasing.entry().mask?;
+                        let shown = erasing.erase_hidden();
+                        v.set_content(&shown);
+                        v.set_cursor(shown.len());
+                        Some(EventResult::consumed())
+                    })
+                    // The masked input is only ever typed at its end, the hidden one can't follow the cursor.
+                    .on_pre_event_inner(
+                        EventTrigger::from_fn(|e| {
+                            matches!(
+                                e,
+                                Event::Key(
+                                    Key::Del
+                                        | Key::Left
+                                        | Key::Right
+                                        | Key::Up
+                                        | Key::Down
+                                        | Key::Home
+                                        | Key::End
+                                        | Key::PageUp
+                                        | Key::PageDown
+                                ) | Event::Ctrl(Key::Home | Key::End)
+                                    | Event::Mouse { .. }
+                            )
+                        }),
+                        move |v, _e| {
+                            let v = v.get_mut();
+                            if !v.is_enabled() {
+                                return None;
+                            }
+                            moving.entry().mask.map(|_| EventResult::consumed())
+                        },
+                    )
+                    .on_pre_event_inner(Key::Up, move |v, _e| {
+                        // Multi-line input moves between its lines.
+                        if previous.entry().multiline {
+                            return None;
+                        }
+                        assisted(v, |text| previous.previous(text))
+                    })
+                    .on_pre_event_inner(Key::Down, move |v, _e| {
+                        if next.entry().multiline {
+                            return None;
+                        }
+                        assisted(v, |_| next.next())
+                    })
+                    .on_pre_event_inner(Key::Tab, move |v, _e| {
+                        assisted(v, |text| assist.complete(text))
+                    })
+                    .full_width(),
+            )
+            .child(TextView::new_with_content(status_content.clone()))
     }
 }
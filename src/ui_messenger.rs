@@ -1,37 +1,87 @@
-use std::sync::mpsc::Receiver;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use cursive::{CbSink, Cursive};
 use cursive::theme::{BaseColor, Color, Effect, Style};
 use cursive::utils::markup::StyledString;
-use cursive::views::{TextArea, TextContent, TextView};
+use cursive::traits::Resizable;
+use cursive::views::{Layer, PaddedView, TextArea, TextContent, TextView};
 use cursive_aligned_view::AlignedView;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+use crate::alignment::{Alignment, Anchor};
+use crate::animation::Playback;
+use crate::canvas::{Canvas, Layers};
 use crate::compiled::CompileError;
-use crate::show_input::Alignment;
+use crate::controls::{AdvanceMode, Controls};
+use crate::frame::Frame;
+use crate::input_assist::{EntryOptions, InputAssist};
+use crate::read_log::ReadLog;
+use crate::settings::Settings;
+use crate::show_input::ShowInput;
 use crate::text_input::TitleInput;
+use crate::time_span::TimeSpan;
+use crate::transition::{Transition, TransitionEffect};
 
 pub struct UIMessenger {
     text_content: TextContent,
-    frame_content: TextContent,
+    canvas: Canvas,
+    status_content: TextContent,
     cb_sink: CbSink,
     input_receiver: Receiver<String>,
+    controls: Controls,
+    assist: InputAssist,
+    read_log: ReadLog,
+    speed: f32,
+    /// The animations playing in the background, by layer.
+    animations: HashMap<String, Playback>,
 }
 
 impl UIMessenger {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         text_content: TextContent,
         frame_content: TextContent,
+        status_content: TextContent,
         cb_sink: CbSink,
         input_receiver: Receiver<String>,
+        controls: Controls,
+        assist: InputAssist,
+        read_log: ReadLog,
     ) -> Self {
         UIMessenger {
             text_content,
-            frame_content,
+            canvas: Canvas::new(frame_content, cb_sink.clone()),
+            status_content,
             cb_sink,
             input_receiver,
+            controls,
+            assist,
+            read_log,
+            speed: 1.0,
+            animations: HashMap::new(),
         }
     }
 
+    pub fn configure(&mut self, settings: &Settings) {
+        self.controls.set_advance_mode(settings.advance);
+        self.speed = settings.speed;
+        self.assist.configure(settings.history, settings.completion);
+    }
+
+    /// What Tab completes the next inputs with.
+    pub fn set_completions(&self, completions: Vec<String>) {
+        self.assist.set_completions(completions);
+    }
+
+    /// Waits for the given time, sped up by the speed setting.
+    pub fn wait(&self, span: TimeSpan) {
+        crate::common::sleep(span.scaled(self.speed));
+    }
+
     pub fn update_ui(&self) {
         self.cb_sink.send(Box::new(Cursive::noop)).unwrap();
     }
@@ -41,9 +91,26 @@ impl UIMessenger {
         self.update_ui();
     }
 
-    pub fn clear_frame(&mut self) {
-        self.frame_content.set_content("");
-        self.update_ui();
+    /// The size of the screen in cells, which the frame takes up.
+    pub fn frame_size(&self) -> (usize, usize) {
+        let (tx, rx) = mpsc::channel();
+        self.cb_sink
+            .send(Box::new(move |s| {
+                let _ = tx.send(s.screen_size());
+            }))
+            .unwrap();
+        rx.recv().map_or((80, 24), |size| (size.x, size.y))
+    }
+
+    pub fn clear_frame(&mut self, transition: Option<Transition>) {
+        self.stop_animations();
+        let transition = self.scaled(transition);
+        self.canvas.change(transition, Layers::clear);
+    }
+
+    /// The transition sped up by the speed setting.
+    fn scaled(&self, transition: Option<Transition>) -> Option<(TransitionEffect, Duration)> {
+        transition.map(|transition| (transition.effect, transition.duration.scaled(self.speed)))
     }
 
     pub fn append<S>(&mut self, s: S)
@@ -77,73 +144,132 @@ impl UIMessenger {
         self.append_titled_err(&(e.name() + "Error"), &e.to_string());
         self.title(&TitleInput {
             text: "ERROR".to_string(),
-            wait: 2,
+            wait: TimeSpan::from_secs(2),
+            anchor: Anchor::default(),
         });
     }
 
-    pub fn set_frame<S>(&mut self, s: S)
-        where
-            S: Into<StyledString>,
-    {
-        self.frame_content.set_content(s);
-        self.update_ui();
+    /// Writes a dialog line, then waits for the player to move on from it according to the advance mode.
+    pub fn say(&mut self, s: StyledString, speed: f32, reading_delay: TimeSpan) {
+        let reading = reading_delay.scaled(self.speed) * s.width() as u32;
+        if !self.typewrite_s(s, speed) {
+            self.wait_for_advance(reading);
+        }
+    }
+
+    fn wait_for_advance(&mut self, reading: Duration) {
+        const BLINK: Duration = Duration::from_millis(500);
+
+        let start = Instant::now();
+        self.controls.listen();
+        while !self.controls.advance_pressed() && !self.controls.fast_forwarding() {
+            let blink = (start.elapsed().as_millis() / BLINK.as_millis()).is_multiple_of(2);
+            let indicator = match self.controls.advance_mode() {
+                AdvanceMode::Off => break,
+                AdvanceMode::Auto if start.elapsed() >= reading => break,
+                AdvanceMode::Auto => "▶",
+                AdvanceMode::Click if blink => "▼",
+                AdvanceMode::Click => " ",
+            };
+            self.set_status(indicator);
+            std::thread::sleep(Duration::from_secs_f32(1.0 / 60.0));
+        }
+        self.controls.reset();
+        self.set_status("");
     }
 
-    pub fn typewrite_s<S>(&mut self, s: S, speed: f32)
+    /// Shows a short status (like the continue indicator) next to the text input.
+    pub fn set_status(&mut self, status: &str) {
+        if self.status_content.get_content().source() != status {
+            self.status_content.set_content(status);
+            self.update_ui();
+        }
+    }
+
+    /// Returns whether the line was skipped because it was already read.
+    pub fn typewrite_s<S>(&mut self, s: S, speed: f32) -> bool
         where
             S: Into<StyledString>,
     {
         let s = s.into();
+        let line = s.source().to_string();
         let l = s.width();
-        self.typewrite(s, l as f32 / speed);
+        // Lines read in an earlier session (or earlier in this one) are written at once when skipping.
+        let skip = self.controls.skip_read() && self.read_log.contains(&line);
+        self.typewrite(s, if skip { 0.0 } else { l as f32 / (speed * self.speed) });
+        self.controls.reset();
+        self.read_log.mark(&line);
         self.text_content.append("\n");
         self.update_ui();
+        skip
     }
 
     // Stolen from snailprint...
+    // Paced by display width and written one grapheme cluster at a time, so accented,
+    // wide (CJK) and emoji text animates evenly and combining marks never show up alone.
+    // Pressing advance completes it at once, holding fast-forward speeds it up.
     pub fn typewrite<S>(&mut self, s: S, duration: f32)
         where
             S: Into<StyledString>,
     {
-        use cursive::utils::span::IndexedSpan;
         use std::thread::sleep;
         use std::time::Instant;
 
-        let time = Instant::now();
+        self.controls.listen();
+        let mut elapsed = 0.0;
+        let mut frame = Instant::now();
 
         let s = s.into();
-        let mut string = s.source().to_string();
+        let graphemes: Vec<(&str, Style)> = s
+            .spans()
+            .flat_map(|span| {
+                let style = *span.attr;
+                span.content.graphemes(true).map(move |g| (g, style))
+            })
+            .collect();
+        let width: usize = graphemes.iter().map(|(g, _)| g.width()).sum();
         let fps = 60.0;
         let delta = 1.0 / fps;
-        let len = string.len();
-        let span: &IndexedSpan<Style> = s.spans_raw().first().unwrap();
-        // TODO: Use the actual styling, per span!! (instead of just the first span...)
-        let style = span.attr;
-
-        'outer: while !s.is_empty() {
-            let char_targ = (len as f32 * time.elapsed().as_secs_f32() / duration) as usize;
-
-            while char_targ > len - string.len() {
-                if !string.is_empty() {
-                    let character = string.remove(0);
-                    self.text_content
-                        .append(StyledString::styled(character, style));
-                    self.update_ui();
-                } else {
-                    // this is so sleep() is not called when this loop breaks
-                    break 'outer;
-                }
+
+        let mut graphemes = graphemes.into_iter().peekable();
+        let mut written = 0;
+        while graphemes.peek().is_some() {
+            elapsed += frame.elapsed().as_secs_f32() * self.controls.speed();
+            frame = Instant::now();
+            let width_targ = if self.controls.advance_pressed() {
+                usize::MAX
+            } else {
+                (width as f32 * elapsed / duration) as usize
+            };
+
+            // Zero-width clusters (stray joiners, control characters...) are written
+            // together with whatever came before them.
+            while let Some((grapheme, style)) =
+                graphemes.next_if(|(g, _)| written < width_targ || g.width() == 0)
+            {
+                written += grapheme.width();
+                self.text_content
+                    .append(StyledString::styled(grapheme, style));
+            }
+            self.update_ui();
+
+            // this is so sleep() is not called after the last cluster was written
+            if graphemes.peek().is_some() {
+                sleep(std::time::Duration::from_secs_f32(delta));
             }
-            sleep(std::time::Duration::from_secs_f32(delta));
         }
     }
 
     pub fn title(&self, input: &TitleInput) {
         let figure = input.figure().to_string();
+        let anchor = input.anchor;
         self.cb_sink
-            .send(Box::new(|s| s.add_layer(TextView::new(figure))))
+            .send(Box::new(move |s| {
+                let title = anchor.wrap(Layer::new(TextView::new(figure)), Alignment::Center);
+                s.screen_mut().add_transparent_layer(title.full_screen());
+            }))
             .unwrap();
-        crate::common::sleep(input.wait);
+        self.wait(input.wait);
         self.cb_sink
             .send(Box::new(|s| {
                 s.pop_layer();
@@ -156,6 +282,8 @@ impl UIMessenger {
             .send(Box::new(move |s| {
                 s.call_on_name("text-input", move |v: &mut TextArea| {
                     if disable {
+                        // Whatever wasn't submitted in time shouldn't carry over to the next input.
+                        v.set_content("");
                         v.disable();
                     } else {
                         v.enable();
@@ -169,27 +297,130 @@ impl UIMessenger {
             .unwrap();
     }
 
-    pub fn get_input(&self) -> String {
+    /// Returns `None` if nothing was submitted before the timeout.
+    pub fn get_input(&mut self, timeout: Option<Duration>, countdown: bool) -> Option<String> {
+        // Drop anything submitted late to an earlier input.
+        while self.input_receiver.try_recv().is_ok() {}
         self.update_text_input(false);
-        let input = self.input_receiver.recv().unwrap();
+        let input = match timeout {
+            Some(timeout) => self.recv_before(Instant::now() + timeout, countdown),
+            None => self.input_receiver.recv().ok(),
+        };
         self.update_text_input(true);
         input
     }
 
-    pub fn get_append_input(&mut self) -> String {
-        let input = self.get_input();
-        self.append(&input);
+    fn recv_before(&mut self, deadline: Instant, countdown: bool) -> Option<String> {
+        const TICK: Duration = Duration::from_millis(100);
+
+        let input = loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break None;
+            }
+            if countdown {
+                self.set_status(&format!("{}s", left.as_secs_f32().ceil()));
+            }
+            match self.input_receiver.recv_timeout(left.min(TICK)) {
+                Ok(input) => break Some(input),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break None,
+            }
+        };
+        self.set_status("");
         input
     }
 
-    pub fn align_frame(&mut self, alignment: Alignment) {
+    pub fn get_append_input(&mut self, timeout: Option<Duration>, countdown: bool) -> Option<String> {
+        self.get_entry_input(timeout, countdown, &EntryOptions::default())
+    }
+
+    /// Reads input the way the options say, then goes back to the default ones.
+    pub fn get_entry_input(
+        &mut self,
+        timeout: Option<Duration>,
+        countdown: bool,
+        entry: &EntryOptions,
+    ) -> Option<String> {
+        self.assist.set_entry(entry.clone());
+        let input = self.get_input(timeout, countdown);
+        self.assist.set_entry(EntryOptions::default());
+        let input = input?;
+        if entry.echo {
+            self.append(entry.masked(&input));
+        }
+        Some(input)
+    }
+
+    /// Plays the frames on the input's layer, which is removed after its duration if given.
+    /// Without a layer, everything else on the frame is cleared first.
+    pub fn animate(&mut self, input: &ShowInput, frames: Vec<(Frame, TimeSpan)>) {
+        let layer = input.layer.as_deref().unwrap_or_default();
+        match input.layer {
+            Some(_) => self.stop_animation(layer),
+            None => self.stop_animations(),
+        }
+        let transition = self.scaled(input.transition);
+        let first = frames.first().map(|(frame, _)| frame.clone());
+        self.canvas.change(transition, |layers| {
+            if input.layer.is_none() {
+                layers.clear();
+            }
+            if let Some(first) = first {
+                layers.set(layer, first, Some(input.placement));
+            }
+        });
+
+        let frames: Vec<_> = frames
+            .into_iter()
+            .map(|(frame, delay)| (frame, delay.scaled(self.speed)))
+            .collect();
+        let looping = input.looping;
+        let duration = input.duration.map(|duration| duration.scaled(self.speed));
+        let playback = Playback::new(self.canvas.clone(), layer);
+        // Looping forever would never let the story go on.
+        if input.background || (looping && duration.is_none()) {
+            self.animations.insert(layer.to_string(), playback.clone());
+            thread::spawn(move || playback.play(&frames, looping, duration));
+        } else {
+            playback.play(&frames, looping, duration);
+        }
+    }
+
+    pub fn stop_animation(&mut self, layer: &str) {
+        if let Some(playback) = self.animations.remove(layer) {
+            playback.stop();
+        }
+    }
+
+    pub fn stop_animations(&mut self) {
+        for (_, playback) in self.animations.drain() {
+            playback.stop();
+        }
+    }
+
+    /// Returns `false` if there's no such layer.
+    pub fn move_layer(
+        &self,
+        layer: &str,
+        x: Option<usize>,
+        y: Option<usize>,
+        z: Option<i32>,
+    ) -> bool {
+        self.canvas.move_layer(layer, x, y, z)
+    }
+
+    /// Returns `false` if there's no such layer.
+    pub fn remove_layer(&mut self, layer: &str) -> bool {
+        self.stop_animation(layer);
+        self.canvas.remove_layer(layer)
+    }
+
+    pub fn align_frame(&mut self, anchor: Anchor) {
         let _ = self.cb_sink.send(Box::new(move |s| {
             s.call_on_name(
                 "frame-view",
-                |v: &mut AlignedView<TextView>| match alignment {
-                    Alignment::Center => v.set_center(),
-                    Alignment::TopLeft => v.set_top_left(),
-                },
+                |v: &mut AlignedView<PaddedView<TextView>>| anchor.apply(v, Alignment::TopLeft),
             );
         }));
     }
@@ -0,0 +1,13 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Named lists of synonyms captures can refer to.
+#[derive(Debug, Deserialize, Default)]
+pub struct Vocabulary(HashMap<String, Vec<String>>);
+
+impl Vocabulary {
+    pub fn get(&self, name: &str) -> Option<&Vec<String>> {
+        self.0.get(name)
+    }
+}
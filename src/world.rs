@@ -0,0 +1,510 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use cursive::theme::Effect;
+use cursive::utils::markup::StyledString;
+use serde::Deserialize;
+
+use crate::capture::Capture;
+use crate::compiled::{Checked, Compiled, CompileError};
+use crate::executable::{Executable, ExecutionState};
+use crate::initializer::{InitializerData, RuntimeState};
+use crate::inventory::{InventoryListing, Item};
+use crate::parser::{Command, Lexicon};
+use crate::section::Section;
+use crate::switcher::Case;
+
+/// Where objects start out when the player already carries them.
+const INVENTORY: &str = "inventory";
+
+/// Verbs handled by every world, with the synonyms added when the lexicon doesn't define them.
+const VERBS: [(&str, &[&str]); 5] = [
+    ("go", &["walk", "move", "head"]),
+    ("look", &["l", "examine", "x", "inspect"]),
+    ("take", &["get", "grab", "pick up"]),
+    ("drop", &["put down", "discard"]),
+    ("inventory", &["i", "inv"]),
+];
+
+const DIRECTIONS: [(&str, &str); 6] = [
+    ("north", "n"),
+    ("south", "s"),
+    ("east", "e"),
+    ("west", "w"),
+    ("up", "u"),
+    ("down", "d"),
+];
+
+/// Locations the player walks between, reading commands until a `!leave` section runs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct World {
+    pub start: String,
+    pub locations: HashMap<String, Location>,
+    #[serde(default)]
+    pub objects: BTreeMap<String, WorldObject>,
+    /// Checked in every location, after the location's own cases and before the built-in commands.
+    #[serde(default)]
+    pub cases: Vec<Case<Capture>>,
+    #[serde(default)]
+    pub messages: WorldMessages,
+    /// Tells the world's state apart from other worlds', given when compiled.
+    #[serde(skip)]
+    key: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Directions mapped to the location they lead to.
+    #[serde(default)]
+    pub exits: BTreeMap<String, String>,
+    /// Runs every time the player walks in.
+    pub enter: Option<Box<Section>>,
+    #[serde(default)]
+    pub cases: Vec<Case<Capture>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldObject {
+    pub name: String,
+    /// Other words the player can use for it, besides its id.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    /// A location id, or `inventory`.
+    pub location: Option<String>,
+    #[serde(default = "WorldObject::default_takeable")]
+    pub takeable: bool,
+}
+
+impl WorldObject {
+    fn default_takeable() -> bool {
+        true
+    }
+}
+
+/// What the built-in commands answer with.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct WorldMessages {
+    pub unknown: String,
+    pub no_exit: String,
+    pub not_here: String,
+    pub cant_take: String,
+    pub already_carried: String,
+    pub taken: String,
+    pub dropped: String,
+    pub not_carried: String,
+    #[serde(flatten)]
+    pub inventory: InventoryListing,
+    pub you_see: String,
+    pub exits: String,
+}
+
+impl Default for WorldMessages {
+    fn default() -> Self {
+        WorldMessages {
+            unknown: "I don't understand that.".to_string(),
+            no_exit: "You can't go that way.".to_string(),
+            not_here: "You don't see that here.".to_string(),
+            cant_take: "You can't take that.".to_string(),
+            already_carried: "You already have that.".to_string(),
+            taken: "Taken.".to_string(),
+            dropped: "Dropped.".to_string(),
+            not_carried: "You aren't carrying that.".to_string(),
+            inventory: InventoryListing::default(),
+            you_see: "You see:".to_string(),
+            exits: "Exits:".to_string(),
+        }
+    }
+}
+
+/// Where the player and the objects are in each world, kept between visits to it.
+#[derive(Debug, Default)]
+pub struct WorldState {
+    /// Keyed by the world's key.
+    visits: HashMap<usize, Visit>,
+    /// The key of the world being explored.
+    current: Option<usize>,
+    leaving: bool,
+}
+
+#[derive(Debug)]
+struct Visit {
+    location: String,
+    /// The location each object lies in.
+    placed: HashMap<String, String>,
+}
+
+impl WorldState {
+    /// Leaves the world being explored once the turn is over, outside of a world it does nothing.
+    pub fn leave(&mut self) {
+        self.leaving = self.current.is_some();
+    }
+}
+
+impl World {
+    fn visit<'a>(&self, state: &'a RuntimeState) -> &'a Visit {
+        &state.world.visits[&self.key]
+    }
+
+    fn visit_mut<'a>(&self, state: &'a mut RuntimeState) -> &'a mut Visit {
+        state.world.visits.get_mut(&self.key).unwrap()
+    }
+
+    fn location(&self, state: &RuntimeState) -> &Location {
+        &self.locations[&self.visit(state).location]
+    }
+
+    fn is_here(&self, object: &str, state: &RuntimeState) -> bool {
+        let visit = self.visit(state);
+        visit.placed.get(object) == Some(&visit.location)
+    }
+
+    /// Starts the first visit, the objects in the inventory are given to the player.
+    fn place_objects(&self, state: &mut RuntimeState) {
+        let mut placed = HashMap::new();
+        for (id, object) in self.objects.iter() {
+            match object.location.as_deref() {
+                Some(INVENTORY) => state.give(id, 1),
+                Some(location) => {
+                    placed.insert(id.to_owned(), location.to_string());
+                }
+                None => (),
+            }
+        }
+        let visit = Visit {
+            location: self.start.clone(),
+            placed,
+        };
+        state.world.visits.insert(self.key, visit);
+    }
+
+    fn enter(&self, id: &str, execution: &mut ExecutionState) {
+        self.visit_mut(execution.state).location = id.to_string();
+        self.describe(execution);
+        if let Some(ref section) = self.locations[id].enter {
+            section.execute(execution);
+        }
+    }
+
+    fn describe(&self, execution: &mut ExecutionState) {
+        let location = self.location(execution.state);
+        let mut lines = vec![StyledString::styled(&location.name, Effect::Bold)];
+        if !location.description.is_empty() {
+            lines.push(execution.state.expand_string(&location.description).into());
+        }
+        let objects: Vec<&str> = self
+            .objects
+            .iter()
+            .filter(|(id, _)| self.is_here(id, execution.state))
+            .map(|(_, object)| object.name.as_str())
+            .collect();
+        if !objects.is_empty() {
+            lines.push(format!("{} {}.", self.messages.you_see, objects.join(", ")).into());
+        }
+        if !location.exits.is_empty() {
+            let exits: Vec<&str> = location.exits.keys().map(String::as_str).collect();
+            lines.push(format!("{} {}.", self.messages.exits, exits.join(", ")).into());
+        }
+        for line in lines {
+            execution.ui.append(line);
+        }
+    }
+
+    fn go(&self, direction: &str, execution: &mut ExecutionState) {
+        match self.location(execution.state).exits.get(direction) {
+            Some(target) => self.enter(&target.to_owned(), execution),
+            None => execution.ui.append(self.messages.no_exit.as_str()),
+        }
+    }
+
+    fn look(&self, id: &str, execution: &mut ExecutionState) {
+        let visible = execution.state.inventory.contains(id) || self.is_here(id, execution.state);
+        match execution.init.items.get(id) {
+            Some(item) if visible => {
+                let description = execution.state.expand_string(&item.description);
+                execution.ui.append(description);
+            }
+            _ => execution.ui.append(self.messages.not_here.as_str()),
+        }
+    }
+
+    /// Items dropped here that aren't the world's objects can always be taken back.
+    fn take(&self, id: &str, state: &mut RuntimeState) -> &str {
+        if state.inventory.contains(id) {
+            &self.messages.already_carried
+        } else if !self.is_here(id, state) {
+            &self.messages.not_here
+        } else if self.objects.get(id).is_some_and(|object| !object.takeable) {
+            &self.messages.cant_take
+        } else {
+            self.visit_mut(state).placed.remove(id);
+            state.give(id, 1);
+            &self.messages.taken
+        }
+    }
+
+    fn drop(&self, id: &str, state: &mut RuntimeState) -> &str {
+        if state.take(id, 1) {
+            let visit = self.visit_mut(state);
+            visit.placed.insert(id.to_string(), visit.location.clone());
+            &self.messages.dropped
+        } else {
+            &self.messages.not_carried
+        }
+    }
+
+    fn list_inventory(&self, execution: &mut ExecutionState) {
+        let inventory = &execution.state.inventory;
+        let line = self.messages.inventory.describe(inventory, &execution.init.items);
+        execution.ui.append(line);
+    }
+
+    /// Runs the built-in command, returns `false` if there is none for it.
+    fn run_command(&self, command: &Command, execution: &mut ExecutionState) -> bool {
+        let object = command.object.as_ref().map(|noun| noun.name.as_str());
+        match (command.verb.as_str(), object) {
+            ("go", Some(direction)) => self.go(direction, execution),
+            ("look", None) => self.describe(execution),
+            ("look", Some(object)) => self.look(object, execution),
+            ("take", Some(object)) => {
+                let message = self.take(object, execution.state);
+                execution.ui.append(message);
+            }
+            ("drop", Some(object)) => {
+                let message = self.drop(object, execution.state);
+                execution.ui.append(message);
+            }
+            ("inventory", None) => self.list_inventory(execution),
+            // Directions are verbs too, so `north` works on its own.
+            (direction, None) if self.is_direction(direction) => self.go(direction, execution),
+            _ => return false,
+        }
+        true
+    }
+
+    fn is_direction(&self, word: &str) -> bool {
+        self.locations
+            .values()
+            .any(|location| location.exits.contains_key(word))
+    }
+
+    fn turn(&self, execution: &mut ExecutionState) {
+        if execution.run_global_command() {
+            return;
+        }
+        let location = self.location(execution.state);
+        let case = location
+            .cases
+            .iter()
+            .chain(self.cases.iter())
+            .find(|case| case.matches(execution.state));
+        if let Some(case) = case {
+            case.execute(execution);
+            return;
+        }
+        let command = execution.state.last_command.clone();
+        let handled = command.is_some_and(|command| self.run_command(&command, execution));
+        if !handled {
+            execution.ui.append(self.messages.unknown.as_str());
+        }
+    }
+
+    fn check(&self) -> Checked {
+        let undefined = |id: &str| CompileError::Undefined("location", id.to_string());
+        if !self.locations.contains_key(&self.start) {
+            return Err(undefined(&self.start));
+        }
+        let mut exits = self.locations.values().flat_map(|l| l.exits.values());
+        if let Some(target) = exits.find(|t| !self.locations.contains_key(*t)) {
+            return Err(undefined(target));
+        }
+        let mut placed = self.objects.values().filter_map(|o| o.location.as_ref());
+        if let Some(location) = placed.find(|l| *l != INVENTORY && !self.locations.contains_key(*l))
+        {
+            return Err(undefined(location));
+        }
+        Ok(())
+    }
+
+    /// Teaches the lexicon the built-in verbs, the directions and the objects' names.
+    fn extend(&self, lexicon: &mut Lexicon) {
+        let words = |synonyms: &[&str]| synonyms.iter().map(|s| s.to_string()).collect();
+        for (verb, synonyms) in VERBS {
+            lexicon
+                .verbs
+                .entry(verb.to_string())
+                .or_insert_with(|| words(synonyms));
+        }
+        for direction in self.locations.values().flat_map(|l| l.exits.keys()) {
+            let short = DIRECTIONS
+                .iter()
+                .find(|(name, _)| name == direction)
+                .map(|(_, short)| vec![short.to_string()])
+                .unwrap_or_default();
+            lexicon
+                .verbs
+                .entry(direction.to_owned())
+                .or_insert_with(|| short.clone());
+            lexicon.nouns.entry(direction.to_owned()).or_insert(short);
+        }
+        for (id, object) in self.objects.iter() {
+            let synonyms = lexicon.nouns.entry(id.to_owned()).or_default();
+            for name in std::iter::once(&object.name).chain(object.aliases.iter()) {
+                if !synonyms.contains(name) {
+                    synonyms.push(name.to_owned());
+                }
+            }
+        }
+        lexicon.normalize();
+    }
+}
+
+impl Executable for World {
+    fn execute(&self, execution: &mut ExecutionState) {
+        if !execution.state.world.visits.contains_key(&self.key) {
+            self.place_objects(execution.state);
+        }
+        // Worlds explored from within another one give it back once left.
+        let outer = execution.state.world.current.replace(self.key);
+        let location = self.visit(execution.state).location.clone();
+        self.enter(&location, execution);
+
+        while !execution.state.world.leaving {
+            let location = self.location(execution.state);
+            execution.offer_completions(location.cases.iter().chain(self.cases.iter()));
+            let input = match execution.ui.get_append_input(None, false) {
+                Some(input) => input,
+                None => break,
+            };
+            execution
+                .state
+                .set_input(input, execution.init.lexicon.get());
+            self.turn(execution);
+        }
+        execution.state.world.leaving = false;
+        execution.state.world.current = outer;
+    }
+}
+
+impl Compiled for World {
+    fn compile(&mut self, init: &mut InitializerData, base: &Path) -> Checked {
+        self.check()?;
+        self.key = init.worlds;
+        init.worlds += 1;
+        // Before the cases, so their commands can use the world's words.
+        self.extend(init.lexicon.get_mut());
+        // Objects are items once carried, unless the init file already defines them.
+        for (id, object) in self.objects.iter() {
+            init.items.entry(id.to_owned()).or_insert_with(|| Item {
+                id: id.to_owned(),
+                name: object.name.to_owned(),
+                description: object.description.to_owned(),
+            });
+        }
+        for location in self.locations.values_mut() {
+            if let Some(ref mut section) = location.enter {
+                section.compile(init, base)?;
+            }
+            for case in location.cases.iter_mut() {
+                case.compile(init, base)?;
+            }
+        }
+        for case in self.cases.iter_mut() {
+            case.compile(init, base)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(start: &str) -> World {
+        serde_yaml::from_str(&format!(
+            "
+            start: {}
+            locations:
+              {}:
+                name: Hall
+            objects:
+              lamp:
+                name: lamp
+                location: {}
+              statue:
+                name: statue
+                location: {}
+                takeable: false
+            ",
+            start, start, start, start
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn dropped_items_can_be_taken_back() {
+        let world = world("hall");
+        let mut state = RuntimeState::default();
+        world.place_objects(&mut state);
+        state.give("coin", 1);
+        assert_eq!(world.drop("coin", &mut state), world.messages.dropped);
+        assert!(!state.inventory.contains("coin"));
+        assert_eq!(world.take("coin", &mut state), world.messages.taken);
+        assert!(state.inventory.contains("coin"));
+        assert_eq!(
+            world.take("coin", &mut state),
+            world.messages.already_carried
+        );
+    }
+
+    #[test]
+    fn taking_objects() {
+        let world = world("hall");
+        let mut state = RuntimeState::default();
+        world.place_objects(&mut state);
+        assert_eq!(world.take("statue", &mut state), world.messages.cant_take);
+        assert_eq!(world.take("ghost", &mut state), world.messages.not_here);
+        assert_eq!(world.take("lamp", &mut state), world.messages.taken);
+        assert!(!world.is_here("lamp", &state));
+    }
+
+    #[test]
+    fn worlds_with_the_same_start_are_apart() {
+        let mut init: InitializerData = serde_yaml::from_str("characters: []").unwrap();
+        let (mut first, mut second) = (world("hall"), world("hall"));
+        first.locations.insert(
+            "garden".to_string(),
+            serde_yaml::from_str("name: Garden").unwrap(),
+        );
+        assert!(first.compile(&mut init, Path::new(".")).is_ok());
+        assert!(second.compile(&mut init, Path::new(".")).is_ok());
+        let mut state = RuntimeState::default();
+        first.place_objects(&mut state);
+        first.visit_mut(&mut state).location = "garden".to_string();
+        assert_eq!(first.take("lamp", &mut state), first.messages.not_here);
+
+        second.place_objects(&mut state);
+        assert_eq!(second.location(&state).name, "Hall");
+        assert_eq!(second.take("lamp", &mut state), second.messages.taken);
+        assert_eq!(first.location(&state).name, "Garden");
+    }
+
+    #[test]
+    fn leaving_outside_of_a_world() {
+        let mut state = WorldState::default();
+        state.leave();
+        assert!(!state.leaving);
+        state.current = Some(0);
+        state.leave();
+        assert!(state.leaving);
+    }
+}